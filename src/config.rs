@@ -0,0 +1,227 @@
+use color_eyre::{
+    Result,
+    eyre::{self, WrapErr},
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf, sync::OnceLock};
+
+use crate::hyprland_config::WindowOptions;
+use crate::shell_command::types::{ApplyMode, SelectWindowBy};
+use crate::system_info::get_config_dir;
+
+const CONFIG_FILE: &str = "config.toml";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Settings read from `config.toml` in the config directory. Every one of them is optional,
+/// and command line flags win over them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Where rule files go, relative to the hyprland config directory
+    pub rules_dir: PathBuf,
+    /// Properties ticked on the select-by page, named like `--select-by`
+    pub select_by: Vec<SelectWindowBy>,
+    /// Actions switched on on the actions page, written like rule text, e.g. `size 800 600`
+    pub actions: Vec<String>,
+    /// What generate does with a new rule when `--apply` isn't given
+    pub apply: ApplyMode,
+    /// Reload hyprland after remove without passing `--reload`
+    pub reload_after_remove: bool,
+    pub overlay: OverlayColors,
+    pub clicks: Clicks,
+}
+
+/// What clicking a window in the overlay does with each button, when picking a single window
+/// for generate. Whatever the command line gives wins: action flags over a button's actions
+/// and `--select-by` over the properties matched on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Clicks {
+    pub left: ClickAction,
+    pub right: ClickAction,
+    pub middle: ClickAction,
+    /// Properties a click that writes the rule straight away matches on
+    pub select_by: Vec<SelectWindowBy>,
+    /// The same with Shift held
+    pub shift_select_by: Vec<SelectWindowBy>,
+}
+
+/// A button's part in [`Clicks`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged, expecting = "\"options\" or a list of actions")]
+pub enum ClickAction {
+    /// Ask on the actions and select-by pages, like Enter does
+    Prompt(OptionsPage),
+    /// Write the rule with these actions without asking, written like rule text
+    Actions(Vec<String>),
+}
+
+/// `"options"`, for [`ClickAction::Prompt`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OptionsPage {
+    #[serde(rename = "options")]
+    Open,
+}
+
+/// Fills of the layer drawn over the screen while picking a window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OverlayColors {
+    /// Over the windows that can be picked
+    pub foreground: Color,
+    /// Over the windows picked so far in multi-select mode
+    pub selected: Color,
+    /// Over the window moved to with the keyboard
+    pub focused: Color,
+    /// Everywhere else
+    pub background: Color,
+}
+
+/// A `#rrggbbaa` color, or `#rrggbb` for an opaque one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: u8,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            rules_dir: PathBuf::from("window-rules"),
+            select_by: vec![SelectWindowBy::Class],
+            actions: vec![],
+            apply: ApplyMode::Off,
+            reload_after_remove: false,
+            overlay: OverlayColors::default(),
+            clicks: Clicks::default(),
+        }
+    }
+}
+
+impl Default for Clicks {
+    fn default() -> Self {
+        Self {
+            left: ClickAction::Actions(vec!["float".to_string()]),
+            right: ClickAction::Actions(vec!["tile".to_string()]),
+            middle: ClickAction::Prompt(OptionsPage::Open),
+            select_by: vec![SelectWindowBy::Title],
+            shift_select_by: vec![SelectWindowBy::Class],
+        }
+    }
+}
+
+impl Default for OverlayColors {
+    fn default() -> Self {
+        Self {
+            foreground: Color {
+                red: 0x40,
+                green: 0x40,
+                blue: 0x40,
+                alpha: 0x2f,
+            },
+            selected: Color {
+                red: 0x33,
+                green: 0x66,
+                blue: 0xcc,
+                alpha: 0x5f,
+            },
+            focused: Color {
+                red: 0xff,
+                green: 0xff,
+                blue: 0xff,
+                alpha: 0x3f,
+            },
+            background: Color {
+                red: 0x00,
+                green: 0x00,
+                blue: 0x00,
+                alpha: 0x00,
+            },
+        }
+    }
+}
+
+impl Color {
+    /// The pixel as the overlay's little endian Argb8888 buffers store it.
+    pub fn argb8888_bytes(&self) -> [u8; 4] {
+        [self.blue, self.green, self.red, self.alpha]
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        let error = || format!("\"{text}\" isn't a #rrggbbaa color");
+        let hex = text.strip_prefix('#').ok_or_else(error)?;
+        if !matches!(hex.len(), 6 | 8) || !hex.chars().all(|digit| digit.is_ascii_hexdigit()) {
+            return Err(error());
+        }
+        let channel = |index: usize| match hex.get(index * 2..index * 2 + 2) {
+            Some(pair) => u8::from_str_radix(pair, 16).map_err(|_| error()),
+            None => Ok(0xff),
+        };
+        Ok(Color {
+            red: channel(0)?,
+            green: channel(1)?,
+            blue: channel(2)?,
+            alpha: channel(3)?,
+        })
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        format!(
+            "#{:02x}{:02x}{:02x}{:02x}",
+            color.red, color.green, color.blue, color.alpha
+        )
+    }
+}
+
+/// Read the config file, if there is one. Called once at startup, so a broken file is reported
+/// before any command runs.
+pub fn init() -> Result<()> {
+    let config = load()?;
+    CONFIG.get_or_init(|| config);
+    Ok(())
+}
+
+/// The settings read by [`init`], or the defaults.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+pub fn config_path() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join(CONFIG_FILE))
+}
+
+fn load() -> Result<Config> {
+    let path = config_path()?;
+    if !fs::exists(&path)? {
+        return Ok(Config::default());
+    }
+    let read_error = || format!("Failed to read {}", path.to_string_lossy());
+    let config: Config = toml::from_str(&fs::read_to_string(&path)?).wrap_err_with(read_error)?;
+    WindowOptions::from_rules(&config.actions).wrap_err_with(read_error)?;
+    for action in [
+        &config.clicks.left,
+        &config.clicks.right,
+        &config.clicks.middle,
+    ] {
+        if let ClickAction::Actions(actions) = action {
+            WindowOptions::from_rules(actions).wrap_err_with(read_error)?;
+        }
+    }
+    if config.clicks.select_by.is_empty() || config.clicks.shift_select_by.is_empty() {
+        return Err(
+            eyre::eyre!("clicks.select_by and clicks.shift_select_by can't be empty")
+                .wrap_err(read_error()),
+        );
+    }
+    Ok(config)
+}
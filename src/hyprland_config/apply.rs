@@ -0,0 +1,130 @@
+use color_eyre::Result;
+use hyprland::data::{Client, Clients, FullscreenMode};
+use hyprland::keyword::Keyword;
+use hyprland::prelude::*;
+use std::{fs, path::Path};
+
+use super::RuleSyntax;
+use super::ipc::{ClientExtras, config_errors, extras_for};
+use super::rule_file::read_rule_file;
+
+/// How a freshly written rule file is handed to the running compositor.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ApplyMode {
+    /// Reload the whole config, which also picks up the new file
+    Reload,
+    /// Send each rule line with `hyprctl keyword`, leaving the rest of the config alone
+    Keyword,
+}
+
+/// Load a rule file into Hyprland, report any config errors it caused, then look at `client`
+/// again to see whether the rule took hold. Rules written without picking a window skip that
+/// last check.
+pub fn apply_rule_file(path: &Path, mode: ApplyMode, client: Option<&Client>) -> Result<()> {
+    let rule_file = read_rule_file(path)?;
+    let has_blocks = rule_file
+        .rules
+        .iter()
+        .any(|rule| rule.syntax == RuleSyntax::Block);
+
+    match (mode, has_blocks) {
+        (ApplyMode::Keyword, false) => {
+            for line in fs::read_to_string(path)?.lines() {
+                let line = line.split('#').next().unwrap_or_default();
+                if let Some((keyword, value)) = line.split_once('=') {
+                    Keyword::set(keyword.trim(), value.trim())?;
+                }
+            }
+            println!("Sent the rules to hyprland");
+        }
+        (ApplyMode::Keyword, true) => {
+            println!("Block rules can't be set with a keyword, reloading instead");
+            hyprland::ctl::reload::call()?;
+            println!("Reloaded hyprland");
+        }
+        (ApplyMode::Reload, _) => {
+            hyprland::ctl::reload::call()?;
+            println!("Reloaded hyprland");
+        }
+    }
+
+    let path_text = path.to_string_lossy();
+    let (file_errors, other_errors): (Vec<String>, Vec<String>) = config_errors()?
+        .into_iter()
+        .partition(|error| error.contains(path_text.as_ref()));
+    match file_errors.is_empty() {
+        true => println!("Hyprland accepted {path_text}"),
+        false => {
+            println!("Hyprland reported errors in {path_text}:");
+            for error in &file_errors {
+                println!("    {error}");
+            }
+        }
+    }
+    if !other_errors.is_empty() {
+        println!(
+            "{} other config error(s) unrelated to this rule, see `hyprctl configerrors`",
+            other_errors.len()
+        );
+    }
+
+    let Some(client) = client else {
+        return Ok(());
+    };
+    let Some(client) = Clients::get()?
+        .into_iter()
+        .find(|current| current.address == client.address)
+    else {
+        println!("The window has been closed since it was picked");
+        return Ok(());
+    };
+    let extras = extras_for(&client)?;
+    for rule in &rule_file.rules {
+        match rule.matches_with(&client, &extras) {
+            true => println!("The rule matches the window"),
+            false => println!("The rule does not match the window"),
+        }
+        for action in &rule.actions {
+            match action_in_effect(action, &client, &extras) {
+                Some(true) => println!("    {action}: in effect"),
+                Some(false) => println!(
+                    "    {action}: not in effect yet, most rules only apply when a window opens"
+                ),
+                None => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether the client's current state reflects an action, `None` when IPC can't tell.
+fn action_in_effect(action: &str, client: &Client, extras: &ClientExtras) -> Option<bool> {
+    let (keyword, value) = match action.split_once(' ') {
+        Some((keyword, value)) => (keyword, value.trim()),
+        None => (action, ""),
+    };
+    match keyword {
+        "float" => Some(client.floating),
+        "tile" => Some(!client.floating),
+        "pin" => Some(client.pinned),
+        "fullscreen" => Some(client.fullscreen != FullscreenMode::None),
+        "workspace" => {
+            let workspace = value.trim_end_matches("silent").trim();
+            Some(
+                client.workspace.id.to_string() == workspace
+                    || client.workspace.name == workspace
+                    || workspace.strip_prefix("name:") == Some(client.workspace.name.as_str()),
+            )
+        }
+        "tag" => {
+            let has_tag = |name: &str| extras.tag_names().any(|tag| tag == name);
+            match value.split_at_checked(1) {
+                Some(("-", name)) => Some(!has_tag(name)),
+                Some(("+", name)) => Some(has_tag(name)),
+                _ => Some(has_tag(value)),
+            }
+        }
+        _ => None,
+    }
+}
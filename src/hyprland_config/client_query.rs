@@ -0,0 +1,93 @@
+use color_eyre::{Result, eyre};
+use hyprland::data::{Client, Clients};
+use hyprland::prelude::*;
+
+use super::matching::matching_clients;
+use super::syntax::{select_by_from_v2_field, v2_field};
+use super::{ClientExtras, SelectWindowBy, client_extras};
+
+/// A window described on the command line instead of clicked on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientQuery {
+    /// `0x55d0c0ffee00`, the `0x` is optional
+    Address(String),
+    Pid(i32),
+    /// The focused window
+    Active,
+    /// Every selector has to match, see [`parse_match`]
+    Matching(Vec<(SelectWindowBy, String)>),
+}
+
+/// Parse a `field=pattern` selector, the field named the windowrulev2 way: `class=^(kitty)$`,
+/// `initialTitle=Steam`, `xwayland=1`.
+pub fn parse_match(selector: &str) -> Result<(SelectWindowBy, String)> {
+    let (field, pattern) = selector
+        .split_once('=')
+        .ok_or_else(|| eyre::eyre!("\"{selector}\" isn't a field=pattern selector"))?;
+    let select_by = select_by_from_v2_field(field.trim())
+        .ok_or_else(|| eyre::eyre!("\"{field}\" isn't a field hyprland matches windows on"))?;
+    Ok((select_by, pattern.trim().to_string()))
+}
+
+/// The one open window `query` describes, along with its extras. Fails when no window, or
+/// more than one, fits.
+pub fn find_client(query: &ClientQuery) -> Result<(Client, ClientExtras)> {
+    let clients = Clients::get()?.to_vec();
+    let mut extras = client_extras()?;
+
+    let found: Vec<&Client> = match query {
+        ClientQuery::Address(address) => clients
+            .iter()
+            .filter(|client| same_address(&client.address.to_string(), address))
+            .collect(),
+        ClientQuery::Pid(pid) => clients.iter().filter(|client| client.pid == *pid).collect(),
+        ClientQuery::Active => match Client::get_active()? {
+            Some(active) => clients
+                .iter()
+                .filter(|client| client.address == active.address)
+                .collect(),
+            None => vec![],
+        },
+        ClientQuery::Matching(matchers) => matching_clients(matchers, &clients, &extras),
+    };
+
+    match found.as_slice() {
+        [client] => {
+            let client_extras = extras
+                .remove(&client.address.to_string())
+                .unwrap_or_default();
+            Ok(((*client).clone(), client_extras))
+        }
+        [] if *query == ClientQuery::Active => Err(eyre::eyre!("No window has focus")),
+        [] => Err(eyre::eyre!("No open window matches {}", query_text(query))),
+        found => {
+            let windows = found
+                .iter()
+                .map(|client| format!("\n  {} {}: {}", client.address, client.class, client.title))
+                .collect::<String>();
+            Err(eyre::eyre!(
+                "{} windows match {}, narrow it down:{windows}",
+                found.len(),
+                query_text(query)
+            ))
+        }
+    }
+}
+
+fn same_address(address: &str, query: &str) -> bool {
+    let strip = |address: &str| address.trim_start_matches("0x").to_lowercase();
+    strip(address) == strip(query)
+}
+
+fn query_text(query: &ClientQuery) -> String {
+    match query {
+        ClientQuery::Address(address) => format!("address {address}"),
+        ClientQuery::Pid(pid) => format!("pid {pid}"),
+        ClientQuery::Active => "the focused window".to_string(),
+        ClientQuery::Matching(matchers) => matchers
+            .iter()
+            .map(|(select_by, pattern)| format!("{}={pattern}", v2_field(select_by)))
+            .collect::<Vec<String>>()
+            .join(" "),
+    }
+}
@@ -0,0 +1,346 @@
+use color_eyre::{Result, eyre};
+use hyprland::data::Client;
+
+use super::rule_file::{ParsedRule, RuleFile, read_rule_files};
+use super::syntax::v2_field;
+use super::{ClientExtras, SelectWindowBy, edit_rule_file, extras_for, preview_rule_edit};
+
+/// What to do with an existing rule that clashes with the one about to be written.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ConflictResolution {
+    /// Fold the new actions into the existing rule, the new ones win where they contradict
+    Merge,
+    /// Swap the existing rule for the new one, keeping its file
+    Replace,
+    /// Leave the existing rule alone and write the new one to its own file
+    Keep,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum ConflictKind {
+    /// The existing rule matches on exactly the same patterns
+    SameSelector,
+    /// Both rules apply to the window and set these `(existing, new)` actions differently
+    Contradicts(Vec<(String, String)>),
+}
+
+/// The actions and selector an existing rule ends up with once a conflict is resolved.
+struct ResolvedRule {
+    rules: Vec<String>,
+    matchers: Vec<(SelectWindowBy, String)>,
+}
+
+/// An existing rule that a new rule for `client` would duplicate or fight with.
+#[derive(Debug)]
+pub struct RuleConflict {
+    pub rule_file: RuleFile,
+    pub rule: ParsedRule,
+    pub kind: ConflictKind,
+}
+
+impl RuleConflict {
+    /// The file contents `resolution` would leave behind, `None` for [`ConflictResolution::Keep`]
+    /// which doesn't touch the existing file.
+    pub fn preview(
+        &self,
+        resolution: ConflictResolution,
+        rules: &[String],
+        matchers: &[(SelectWindowBy, String)],
+    ) -> Result<Option<String>> {
+        let Some(resolved) = self.resolved(resolution, rules, matchers)? else {
+            return Ok(None);
+        };
+        preview_rule_edit(
+            &self.rule_file,
+            &self.rule,
+            &resolved.rules,
+            &resolved.matchers,
+        )
+        .map(Some)
+    }
+
+    /// Write `resolution` into the existing file. Returns `false` for
+    /// [`ConflictResolution::Keep`], where the new rule still needs a file of its own.
+    pub fn resolve(
+        &self,
+        resolution: ConflictResolution,
+        rules: &[String],
+        matchers: &[(SelectWindowBy, String)],
+        client: Option<&Client>,
+    ) -> Result<bool> {
+        match self.resolved(resolution, rules, matchers)? {
+            Some(resolved) => {
+                edit_rule_file(
+                    &self.rule_file,
+                    &self.rule,
+                    &resolved.rules,
+                    &resolved.matchers,
+                    client,
+                )?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// What the existing rule becomes, `None` when it stays as it is.
+    fn resolved(
+        &self,
+        resolution: ConflictResolution,
+        rules: &[String],
+        matchers: &[(SelectWindowBy, String)],
+    ) -> Result<Option<ResolvedRule>> {
+        match resolution {
+            ConflictResolution::Merge => {
+                let existing_matchers = self.rule.matchers().ok_or_else(|| {
+                    eyre::eyre!(
+                        "{} matches on a field the editor doesn't know and can't be merged into",
+                        self.rule_file.file_name()
+                    )
+                })?;
+                Ok(Some(ResolvedRule {
+                    rules: merged_rules(&self.rule.actions, rules),
+                    matchers: existing_matchers,
+                }))
+            }
+            ConflictResolution::Replace => Ok(Some(ResolvedRule {
+                rules: rules.to_vec(),
+                matchers: matchers.to_vec(),
+            })),
+            ConflictResolution::Keep => Ok(None),
+        }
+    }
+}
+
+/// Existing rules that share the new rule's selector, or that also apply to `client` and set
+/// one of the same actions differently, e.g. `float` against `tile`. Without a client only
+/// the rules sharing the selector are found.
+pub fn find_conflicts(
+    client: Option<&Client>,
+    matchers: &[(SelectWindowBy, String)],
+    rules: &[String],
+) -> Result<Vec<RuleConflict>> {
+    // Fetched once, tag, xdgTag and content selectors need them to match the client
+    let client = match client {
+        Some(client) => Some((client, extras_for(client)?)),
+        None => None,
+    };
+    let client = client.as_ref().map(|(client, extras)| (*client, extras));
+    Ok(conflicts_in(&read_rule_files()?, client, matchers, rules))
+}
+
+/// [`find_conflicts`] over `rule_files`, with the client's extras already fetched.
+fn conflicts_in(
+    rule_files: &[RuleFile],
+    client: Option<(&Client, &ClientExtras)>,
+    matchers: &[(SelectWindowBy, String)],
+    rules: &[String],
+) -> Vec<RuleConflict> {
+    let mut selector: Vec<(String, String)> = matchers
+        .iter()
+        .map(|(select_by, pattern)| (v2_field(select_by).to_string(), pattern.clone()))
+        .collect();
+    selector.sort();
+
+    let mut conflicts = vec![];
+    for rule_file in rule_files {
+        for rule in &rule_file.rules {
+            let mut rule_selector = rule.selector.clone();
+            rule_selector.sort();
+            let kind = if rule_selector == selector {
+                ConflictKind::SameSelector
+            } else if client.is_some_and(|(client, extras)| rule.matches_with(client, extras)) {
+                let contradictions = contradictions(&rule.actions, rules);
+                if contradictions.is_empty() {
+                    continue;
+                }
+                ConflictKind::Contradicts(contradictions)
+            } else {
+                continue;
+            };
+            conflicts.push(RuleConflict {
+                rule_file: rule_file.clone(),
+                rule: rule.clone(),
+                kind,
+            });
+        }
+    }
+    // Exact duplicates are the likeliest thing to merge into, so they're offered first
+    conflicts.sort_by_key(|conflict| conflict.kind != ConflictKind::SameSelector);
+    conflicts
+}
+
+/// The existing actions with the new ones laid over them, dropping any existing action the
+/// new rule contradicts.
+fn merged_rules(existing: &[String], new: &[String]) -> Vec<String> {
+    existing
+        .iter()
+        .filter(|existing| !new.iter().any(|new| contradicts(existing, new)))
+        .filter(|existing| !new.contains(existing))
+        .chain(new)
+        .cloned()
+        .collect()
+}
+
+fn contradictions(existing: &[String], new: &[String]) -> Vec<(String, String)> {
+    existing
+        .iter()
+        .flat_map(|existing| {
+            new.iter()
+                .filter(|new| contradicts(existing, new))
+                .map(|new| (existing.clone(), new.clone()))
+        })
+        .collect()
+}
+
+/// Two actions contradict when they are float and tile, or the same keyword with a different
+/// value such as `size 800 600` and `size 400 300`. Tags add up, so only adding and removing
+/// the same tag contradicts.
+fn contradicts(existing: &str, new: &str) -> bool {
+    let keyword = |rule: &str| {
+        rule.split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    };
+    let (existing_keyword, new_keyword) = (keyword(existing), keyword(new));
+    match (existing_keyword.as_str(), new_keyword.as_str()) {
+        ("float", "tile") | ("tile", "float") => true,
+        ("tag", "tag") => {
+            let tag_name = |rule: &str| {
+                rule.split_whitespace()
+                    .nth(1)
+                    .unwrap_or_default()
+                    .trim_start_matches(['+', '-'])
+                    .to_string()
+            };
+            tag_name(existing) == tag_name(new) && existing.trim() != new.trim()
+        }
+        _ => existing_keyword == new_keyword && existing.trim() != new.trim(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::hyprland_config::RuleSyntax;
+    use crate::hyprland_config::rule_file::read_rule_file;
+    use crate::test_client::client;
+
+    const SYNTAXES: [RuleSyntax; 3] = [RuleSyntax::V1, RuleSyntax::V2, RuleSyntax::Block];
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn class(class: &str) -> Vec<(SelectWindowBy, String)> {
+        vec![(SelectWindowBy::Class, format!("^({class})$"))]
+    }
+
+    /// A kitty rule that floats and sizes the window, and a foot rule that floats it, in one
+    /// file written the `syntax` way. The directory has to outlive the file.
+    fn existing_rules(syntax: &RuleSyntax) -> (tempfile::TempDir, RuleFile) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("existing.conf");
+        let kitty = syntax.window_rule(
+            "kitty",
+            &strings(&["float", "size 800 600"]),
+            &class("kitty"),
+        );
+        let foot = syntax.window_rule("foot", &strings(&["float"]), &class("foot"));
+        fs::write(&path, kitty.unwrap() + &foot.unwrap()).unwrap();
+        let rule_file = read_rule_file(&path).unwrap();
+        (dir, rule_file)
+    }
+
+    #[test]
+    fn finds_the_rule_with_the_same_selector() {
+        for syntax in &SYNTAXES {
+            let (_dir, rule_file) = existing_rules(syntax);
+            let conflicts = conflicts_in(&[rule_file], None, &class("kitty"), &strings(&["pin"]));
+            assert_eq!(conflicts.len(), 1, "{syntax:?}");
+            assert_eq!(conflicts[0].kind, ConflictKind::SameSelector);
+            assert_eq!(
+                conflicts[0].rule.actions,
+                strings(&["float", "size 800 600"])
+            );
+        }
+    }
+
+    #[test]
+    fn finds_overlapping_rules_only_when_they_contradict() {
+        let kitty = client("0x1", "kitty", "notes");
+        let extras = ClientExtras::default();
+        let title = vec![(SelectWindowBy::Title, "^(notes)$".to_string())];
+        for syntax in &SYNTAXES {
+            let (_dir, rule_file) = existing_rules(syntax);
+            let rule_files = [rule_file];
+
+            let conflicts = conflicts_in(
+                &rule_files,
+                Some((&kitty, &extras)),
+                &title,
+                &strings(&["tile", "size 400 300", "pin"]),
+            );
+            assert_eq!(conflicts.len(), 1, "{syntax:?}");
+            assert_eq!(
+                conflicts[0].kind,
+                ConflictKind::Contradicts(vec![
+                    ("float".to_string(), "tile".to_string()),
+                    ("size 800 600".to_string(), "size 400 300".to_string()),
+                ])
+            );
+
+            let agreeing = strings(&["float", "pin"]);
+            assert!(
+                conflicts_in(&rule_files, Some((&kitty, &extras)), &title, &agreeing).is_empty()
+            );
+            // Without a window to check the rules against, only the selector counts
+            let tile = strings(&["tile"]);
+            assert!(conflicts_in(&rule_files, None, &title, &tile).is_empty());
+        }
+    }
+
+    #[test]
+    fn merge_and_replace_rewrite_only_the_conflicting_rule() {
+        let foot_rule = |syntax: &RuleSyntax| {
+            syntax
+                .window_rule("foot", &strings(&["float"]), &class("foot"))
+                .unwrap()
+        };
+        for syntax in &SYNTAXES {
+            let (_dir, rule_file) = existing_rules(syntax);
+            let conflict = conflicts_in(&[rule_file], None, &class("kitty"), &[])
+                .pop()
+                .unwrap();
+            let new_rules = strings(&["tile", "center"]);
+            let title = vec![(SelectWindowBy::Title, "^(notes)$".to_string())];
+
+            // The new actions win over the contradicted float, the selector stays
+            let merged = conflict
+                .preview(ConflictResolution::Merge, &new_rules, &title)
+                .unwrap();
+            let expected = syntax
+                .window_rule(
+                    "kitty",
+                    &strings(&["size 800 600", "tile", "center"]),
+                    &class("kitty"),
+                )
+                .unwrap();
+            assert_eq!(merged, Some(expected + &foot_rule(syntax)), "{syntax:?}");
+
+            let replaced = conflict
+                .preview(ConflictResolution::Replace, &new_rules, &title)
+                .unwrap();
+            let expected = syntax.window_rule("kitty", &new_rules, &title).unwrap();
+            assert_eq!(replaced, Some(expected + &foot_rule(syntax)), "{syntax:?}");
+
+            let kept = conflict
+                .preview(ConflictResolution::Keep, &new_rules, &title)
+                .unwrap();
+            assert_eq!(kept, None);
+        }
+    }
+}
@@ -0,0 +1,148 @@
+/// Turn a literal window property (title, class, ...) into an anchored regex that matches
+/// exactly that value and can be written into any of the [`super::RuleSyntax`] backends.
+pub fn literal_matcher(value: &str) -> String {
+    format!("^({})$", escape_regex(value))
+}
+
+/// Like [`literal_matcher`], for a pattern matching exactly one of `values`.
+pub fn literal_alternation(values: &[String]) -> String {
+    let values: Vec<String> = values.iter().map(|value| escape_regex(value)).collect();
+    format!("^({})$", values.join("|"))
+}
+
+/// Escape regex metacharacters, and hex-escape anything that means something to hyprlang or
+/// to Hyprland's rule parser so it never reaches them as a raw character:
+///
+/// - `#` starts a comment
+/// - `$` expands variables and `{` / `}` open `{{expressions}}`
+/// - `,` separates rule fields and `:` separates a field from its value
+/// - a trailing `\` continues the value on the next line
+/// - newlines and other control characters end the value
+fn escape_regex(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for character in value.chars() {
+        match character {
+            // Every control character is below U+00A0, so two hex digits always fit
+            character if "#${},:\\\"".contains(character) || character.is_control() => {
+                escaped.push_str(&format!("\\x{:02x}", character as u32));
+            }
+            '.' | '+' | '*' | '?' | '(' | ')' | '|' | '[' | ']' | '^' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use hyprlang::Hyprland;
+    use regex::Regex;
+
+    use super::literal_matcher;
+    use crate::hyprland_config::matching::pattern_matches;
+    use crate::hyprland_config::rule_file::parsed_rules;
+    use crate::hyprland_config::{RuleSyntax, SelectWindowBy};
+
+    /// Titles and classes with everything hyprlang, Hyprland's rule parser or the regex engine
+    /// could read as syntax.
+    const HOSTILE: [&str; 18] = [
+        "# not a comment",
+        "$HOME and $var",
+        "{{1 + 1}} and {braces}",
+        "a, b, c",
+        "key: value",
+        "\"quoted\"",
+        "trailing backslash \\",
+        "C:\\Users\\me",
+        ".+*?()|[]^$",
+        "(unbalanced",
+        "a|b",
+        "tab\tand\nnewline",
+        "bell\x07 and delete\x7f",
+        "next line \u{85}",
+        "Café — naïve",
+        "日本語のタイトル 🎉",
+        "  padded  ",
+        "mixed #$,:{}\"\\\n.*",
+    ];
+
+    /// Render a rule matching `value` literally in `syntax`, parse it back and return the
+    /// pattern Hyprland would see for `field`.
+    fn round_trip(syntax: RuleSyntax, select_by: SelectWindowBy, value: &str) -> String {
+        let matcher = literal_matcher(value);
+        let text = syntax
+            .window_rule(
+                "hostile",
+                &["float".to_string()],
+                &[(select_by, matcher.clone())],
+            )
+            .unwrap();
+        let mut hypr = Hyprland::new();
+        hypr.parse(&text)
+            .unwrap_or_else(|error| panic!("{text:?} didn't parse: {error}"));
+        let rules = parsed_rules(&hypr).unwrap();
+        let [rule] = rules.as_slice() else {
+            panic!("{text:?} parsed into {rules:?}");
+        };
+        let [(_, pattern)] = rule.selector.as_slice() else {
+            panic!("{text:?} parsed into {rule:?}");
+        };
+        assert_eq!(
+            *pattern, matcher,
+            "{text:?} changed on the way through hyprlang"
+        );
+        pattern.clone()
+    }
+
+    fn assert_matches_only(pattern: &str, value: &str) {
+        assert!(
+            Regex::new(pattern).unwrap().is_match(value),
+            "{pattern:?} doesn't match {value:?}"
+        );
+        assert!(
+            pattern_matches(pattern, value),
+            "{pattern:?} doesn't match {value:?}"
+        );
+        assert!(
+            !pattern_matches(pattern, &format!("{value}x")),
+            "{pattern:?} isn't anchored"
+        );
+    }
+
+    #[test]
+    fn hostile_titles_round_trip_through_every_syntax() {
+        for syntax in [RuleSyntax::V1, RuleSyntax::V2, RuleSyntax::Block] {
+            for value in HOSTILE {
+                let pattern = round_trip(syntax, SelectWindowBy::Title, value);
+                assert_matches_only(&pattern, value);
+            }
+        }
+    }
+
+    #[test]
+    fn hostile_classes_round_trip_through_every_syntax() {
+        for syntax in [RuleSyntax::V1, RuleSyntax::V2, RuleSyntax::Block] {
+            for value in HOSTILE {
+                let pattern = round_trip(syntax, SelectWindowBy::Class, value);
+                assert_matches_only(&pattern, value);
+            }
+        }
+    }
+
+    #[test]
+    fn syntax_characters_never_reach_the_parsers_raw() {
+        for value in HOSTILE {
+            let matcher = literal_matcher(value);
+            let inner = &matcher[2..matcher.len() - 2];
+            assert!(
+                !inner
+                    .chars()
+                    .any(|character| "#${},:\"".contains(character) || character.is_control()),
+                "{matcher:?} carries a raw syntax character"
+            );
+        }
+    }
+}
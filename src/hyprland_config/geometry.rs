@@ -0,0 +1,71 @@
+use color_eyre::{Result, eyre};
+use hyprland::data::{Client, Monitor, Monitors, Transforms};
+
+use super::WindowAction;
+
+/// How remembered window geometry is written into `size` and `move` rules.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum GeometryUnits {
+    /// Layout pixels, e.g. `move 100 50`
+    Pixels,
+    /// Percentages of the monitor, e.g. `move 5% 4.6%`, so the rule survives resolution changes
+    Percent,
+}
+
+/// Turn where a window currently sits into `size` and `move` actions. Hyprland reads `move`
+/// relative to the window's monitor, so the position is offset the same way the window
+/// selector offsets its regions.
+pub fn geometry_actions(
+    client: &Client,
+    monitors: &Monitors,
+    units: GeometryUnits,
+) -> Result<Vec<WindowAction>> {
+    let monitor = client
+        .monitor
+        .and_then(|monitor_id| monitors.iter().find(|monitor| monitor.id == monitor_id))
+        .ok_or_else(|| eyre::eyre!("Unable to find the monitor \"{}\" is on", client.title))?;
+
+    let x = client.at.0 as i32 - monitor.x;
+    let y = client.at.1 as i32 - monitor.y;
+    let (width, height) = (client.size.0 as i32, client.size.1 as i32);
+
+    let (size, position) = match units {
+        GeometryUnits::Pixels => (format!("{width} {height}"), format!("{x} {y}")),
+        GeometryUnits::Percent => {
+            let (monitor_width, monitor_height) = logical_size(monitor);
+            (
+                format!(
+                    "{} {}",
+                    percent(width, monitor_width),
+                    percent(height, monitor_height)
+                ),
+                format!(
+                    "{} {}",
+                    percent(x, monitor_width),
+                    percent(y, monitor_height)
+                ),
+            )
+        }
+    };
+    Ok(vec![WindowAction::Size(size), WindowAction::Move(position)])
+}
+
+/// Client coordinates are in layout pixels while the monitor reports its mode in physical
+/// pixels, so undo the scale and any rotation before comparing them.
+fn logical_size(monitor: &Monitor) -> (f32, f32) {
+    let width = monitor.width as f32 / monitor.scale;
+    let height = monitor.height as f32 / monitor.scale;
+    match monitor.transform {
+        Transforms::Normal90
+        | Transforms::Normal270
+        | Transforms::Flipped90
+        | Transforms::Flipped270 => (height, width),
+        _ => (width, height),
+    }
+}
+
+/// `12.5%`, with a trailing `.0` dropped
+fn percent(value: i32, total: f32) -> String {
+    let percent = format!("{:.1}", value as f32 * 100.0 / total);
+    format!("{}%", percent.trim_end_matches(".0"))
+}
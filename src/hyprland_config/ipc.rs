@@ -0,0 +1,86 @@
+use color_eyre::{Result, eyre};
+use hyprland::data::Client;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    env,
+    io::{Read, Write},
+    os::unix::net::UnixStream,
+    path::PathBuf,
+};
+
+/// Send a raw request to Hyprland's command socket, for the requests the `hyprland` crate
+/// doesn't wrap. Prefix `command` with `j/` to get JSON back.
+pub fn request(command: &str) -> Result<String> {
+    let mut stream = UnixStream::connect(socket_path()?)?;
+    stream.write_all(command.as_bytes())?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    Ok(response)
+}
+
+/// The errors Hyprland found the last time it loaded its config.
+pub fn config_errors() -> Result<Vec<String>> {
+    let errors: Vec<String> = serde_json::from_str(&request("j/configerrors")?)?;
+    // An error free config comes back as a single empty string
+    Ok(errors
+        .into_iter()
+        .filter(|error| !error.trim().is_empty())
+        .collect())
+}
+
+/// Client properties `j/clients` reports that the `hyprland` crate's [`Client`] doesn't carry.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientExtras {
+    /// Tags set by `tagwindow` or tag rules, dynamic ones end in `*`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub xdg_tag: String,
+    /// `none`, `photo`, `video` or `game`
+    #[serde(default)]
+    pub content_type: String,
+}
+
+impl ClientExtras {
+    /// The tag names without the `*` dynamic tags are listed with.
+    pub fn tag_names(&self) -> impl Iterator<Item = &str> {
+        self.tags.iter().map(|tag| tag.trim_end_matches('*'))
+    }
+}
+
+#[derive(Deserialize)]
+struct RawClient {
+    address: String,
+    #[serde(flatten)]
+    extras: ClientExtras,
+}
+
+/// [`ClientExtras`] for every open client, keyed by the client's address.
+pub fn client_extras() -> Result<HashMap<String, ClientExtras>> {
+    let clients: Vec<RawClient> = serde_json::from_str(&request("j/clients")?)?;
+    Ok(clients
+        .into_iter()
+        .map(|client| (client.address, client.extras))
+        .collect())
+}
+
+/// [`ClientExtras`] for a single client, empty when it has closed in the meantime.
+pub fn extras_for(client: &Client) -> Result<ClientExtras> {
+    Ok(client_extras()?
+        .remove(&client.address.to_string())
+        .unwrap_or_default())
+}
+
+/// Same lookup the `hyprland` crate does, which keeps its own version private.
+fn socket_path() -> Result<PathBuf> {
+    let runtime_dir = match (env::var_os("XDG_RUNTIME_DIR"), env::var("UID")) {
+        (Some(runtime_dir), _) => PathBuf::from(runtime_dir),
+        (None, Ok(uid)) => PathBuf::from(format!("/run/user/{uid}")),
+        (None, Err(_)) => return Err(eyre::eyre!("Could not find XDG_RUNTIME_DIR or UID")),
+    };
+    let instance = env::var("HYPRLAND_INSTANCE_SIGNATURE")
+        .map_err(|_| eyre::eyre!("HYPRLAND_INSTANCE_SIGNATURE isn't set, is Hyprland running?"))?;
+    Ok(runtime_dir.join("hypr").join(instance).join(".socket.sock"))
+}
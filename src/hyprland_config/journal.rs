@@ -0,0 +1,207 @@
+use color_eyre::{Result, eyre::WrapErr};
+use hyprland::data::Client;
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::system_info::get_data_dir;
+
+const JOURNAL_FILE: &str = "journal.jsonl";
+
+/// What a journaled change did to its file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Create,
+    Overwrite,
+    Delete,
+}
+
+/// The window a change was made for, enough to recognise it again in `history`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientInfo {
+    pub address: String,
+    pub class: String,
+    pub title: String,
+    pub initial_class: String,
+    pub initial_title: String,
+}
+
+/// One line of the journal: a file this tool wrote or deleted and how to put it back.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Seconds since the unix epoch
+    pub timestamp: u64,
+    pub kind: ChangeKind,
+    pub path: PathBuf,
+    /// The file before the change, `None` if it didn't exist
+    pub previous: Option<String>,
+    /// The file after the change, `None` if it was deleted
+    pub contents: Option<String>,
+    /// The command line that made the change
+    pub command: String,
+    pub client: Option<ClientInfo>,
+}
+
+impl From<&Client> for ClientInfo {
+    fn from(client: &Client) -> Self {
+        Self {
+            address: client.address.to_string(),
+            class: client.class.clone(),
+            title: client.title.clone(),
+            initial_class: client.initial_class.clone(),
+            initial_title: client.initial_title.clone(),
+        }
+    }
+}
+
+impl JournalEntry {
+    /// `2026-10-18T09:30:00Z`
+    pub fn time_text(&self) -> String {
+        humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(self.timestamp))
+            .to_string()
+    }
+
+    /// What the file holds now, `None` if it doesn't exist. When that isn't [`Self::contents`]
+    /// something else has written to it since and undoing the change would throw that away.
+    pub fn current_contents(&self) -> Result<Option<String>> {
+        read_existing(&self.path)
+    }
+
+    /// Put the file back the way it was before this change.
+    pub fn restore(&self) -> Result<()> {
+        match &self.previous {
+            Some(previous) => {
+                if let Some(parent) = self.path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&self.path, previous)?;
+            }
+            None if fs::exists(&self.path)? => fs::remove_file(&self.path)?,
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+/// Write `contents` to `path`, recording the change first so it can be undone. `client` is
+/// the window the change was made for, if there was one.
+pub fn write_file(path: &Path, contents: &str, client: Option<&Client>) -> Result<()> {
+    write_file_in(&get_data_dir()?, path, contents, client)
+}
+
+/// [`write_file`] with the journal kept in `data_dir`.
+pub fn write_file_in(
+    data_dir: &Path,
+    path: &Path,
+    contents: &str,
+    client: Option<&Client>,
+) -> Result<()> {
+    let previous = read_existing(path)?;
+    let kind = match previous {
+        Some(_) => ChangeKind::Overwrite,
+        None => ChangeKind::Create,
+    };
+    let journal_path = data_dir.join(JOURNAL_FILE);
+    record(
+        &journal_path,
+        kind,
+        path,
+        previous,
+        Some(contents.to_string()),
+        client,
+    )?;
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+/// Delete `path`, keeping its contents in the journal.
+pub fn remove_file(path: &Path, client: Option<&Client>) -> Result<()> {
+    let previous = read_existing(path)?;
+    record(
+        &journal_path()?,
+        ChangeKind::Delete,
+        path,
+        previous,
+        None,
+        client,
+    )?;
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+/// Every journaled change, oldest first.
+pub fn read_journal() -> Result<Vec<JournalEntry>> {
+    let path = journal_path()?;
+    if !fs::exists(&path)? {
+        return Ok(vec![]);
+    }
+    fs::read_to_string(&path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line)
+                .wrap_err_with(|| format!("Failed to read {}", path.to_string_lossy()))
+        })
+        .collect()
+}
+
+/// Drop everything after the first `length` entries, once they've been undone.
+pub fn truncate_journal(length: usize) -> Result<()> {
+    let entries = read_journal()?;
+    let lines = entries
+        .iter()
+        .take(length)
+        .map(|entry| Ok(format!("{}\n", serde_json::to_string(entry)?)))
+        .collect::<Result<String>>()?;
+    fs::write(journal_path()?, lines)?;
+    Ok(())
+}
+
+/// Append the change to the journal at `journal_path`.
+fn record(
+    journal_path: &Path,
+    kind: ChangeKind,
+    path: &Path,
+    previous: Option<String>,
+    contents: Option<String>,
+    client: Option<&Client>,
+) -> Result<()> {
+    let entry = JournalEntry {
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        kind,
+        // Undo has to find the file again from wherever it's run
+        path: std::path::absolute(path)?,
+        previous,
+        contents,
+        command: env::args().collect::<Vec<String>>().join(" "),
+        client: client.map(ClientInfo::from),
+    };
+
+    if let Some(parent) = journal_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut journal = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(journal_path)
+        .wrap_err_with(|| format!("Failed to open {}", journal_path.to_string_lossy()))?;
+    writeln!(journal, "{}", serde_json::to_string(&entry)?)?;
+    Ok(())
+}
+
+fn read_existing(path: &Path) -> Result<Option<String>> {
+    match fs::exists(path)? {
+        true => Ok(Some(fs::read_to_string(path)?)),
+        false => Ok(None),
+    }
+}
+
+fn journal_path() -> Result<PathBuf> {
+    Ok(get_data_dir()?.join(JOURNAL_FILE))
+}
@@ -0,0 +1,134 @@
+use color_eyre::{Result, eyre};
+use hyprland::data::Layers;
+use std::path::PathBuf;
+
+use super::{RuleOutput, RuleSyntax, hashed_rule_name, literal_matcher, write_rule_file};
+
+/// Effects a `layerrule` can have on a layer-shell surface, named after the keyword they
+/// produce.
+#[derive(PartialEq, Debug, Clone)]
+pub enum LayerAction {
+    Blur,
+    IgnoreZero,
+    /// Ignore pixels below this alpha when blurring, `0` to `1`
+    IgnoreAlpha(f32),
+    NoAnim,
+    /// An animation style such as `slide top` or `popin 80%`
+    Animation(String),
+    DimAround,
+    Xray(bool),
+    Order(i32),
+}
+
+/// Hyprland's layer-shell levels, indexed by the level keys in its `layers` data.
+const LEVELS: [&str; 4] = ["background", "bottom", "top", "overlay"];
+
+/// A layer-shell surface flattened out of [`Layers`], which nests them per monitor and level.
+#[derive(Debug, Clone)]
+pub struct LayerSurface {
+    pub monitor: String,
+    pub level: &'static str,
+    pub namespace: String,
+    pub width: i16,
+    pub height: i16,
+}
+
+/// Every layer surface, sorted so surfaces on the same monitor and level sit next to each other.
+pub fn layer_surfaces(layers: Layers) -> Vec<LayerSurface> {
+    let mut surfaces: Vec<(usize, LayerSurface)> = layers
+        .into_iter()
+        .flat_map(|(monitor, display)| {
+            display
+                .levels
+                .into_iter()
+                .flat_map(move |(level, clients)| {
+                    let level_index = level.parse::<usize>().unwrap_or(0).min(LEVELS.len() - 1);
+                    let monitor = monitor.clone();
+                    clients.into_iter().map(move |client| {
+                        (
+                            level_index,
+                            LayerSurface {
+                                monitor: monitor.clone(),
+                                level: LEVELS[level_index],
+                                namespace: client.namespace,
+                                width: client.w,
+                                height: client.h,
+                            },
+                        )
+                    })
+                })
+        })
+        .collect();
+    surfaces.sort_by(|(level_a, a), (level_b, b)| {
+        (&a.monitor, level_a, &a.namespace).cmp(&(&b.monitor, level_b, &b.namespace))
+    });
+    surfaces.into_iter().map(|(_, surface)| surface).collect()
+}
+
+impl LayerAction {
+    pub fn from_rule(rule: &str) -> Option<Self> {
+        let rule = rule.trim();
+        let (keyword, value) = match rule.split_once(char::is_whitespace) {
+            Some((keyword, value)) => (keyword, Some(value.trim())),
+            None => (rule, None),
+        };
+        let action = match (keyword, value) {
+            ("blur", None) => LayerAction::Blur,
+            ("ignorezero", None) => LayerAction::IgnoreZero,
+            ("ignorealpha", Some(alpha)) => LayerAction::IgnoreAlpha(
+                alpha
+                    .parse()
+                    .ok()
+                    .filter(|alpha| (0.0..=1.0).contains(alpha))?,
+            ),
+            ("noanim", None) => LayerAction::NoAnim,
+            ("animation", Some(style)) => LayerAction::Animation(style.to_string()),
+            ("dimaround", None) => LayerAction::DimAround,
+            ("xray", Some("1")) => LayerAction::Xray(true),
+            ("xray", Some("0")) => LayerAction::Xray(false),
+            ("order", Some(order)) => LayerAction::Order(order.parse().ok()?),
+            _ => return None,
+        };
+        Some(action)
+    }
+
+    pub fn rule(&self) -> String {
+        match self {
+            LayerAction::Blur => "blur".to_string(),
+            LayerAction::IgnoreZero => "ignorezero".to_string(),
+            LayerAction::IgnoreAlpha(alpha) => format!("ignorealpha {alpha}"),
+            LayerAction::NoAnim => "noanim".to_string(),
+            LayerAction::Animation(style) => format!("animation {style}"),
+            LayerAction::DimAround => "dimaround".to_string(),
+            LayerAction::Xray(xray) => format!("xray {}", *xray as u8),
+            LayerAction::Order(order) => format!("order {order}"),
+        }
+    }
+}
+
+/// `layer-{namespace}-{hash}`, the hash telling apart namespaces that differ only in
+/// characters a file name can't hold.
+pub fn default_layer_rule_name(namespace: &str) -> String {
+    hashed_rule_name(&format!("layer-{namespace}"), namespace)
+}
+
+/// Write a layer rule for `namespace` to `{rule_name}.conf`, see [`default_layer_rule_name`].
+pub fn create_layer_rule_config(
+    rule_name: &str,
+    namespace: &str,
+    actions: &[LayerAction],
+    syntax: RuleSyntax,
+    output: RuleOutput,
+) -> Result<PathBuf> {
+    if actions.is_empty() {
+        return Err(eyre::eyre!("A layer rule needs at least one action"));
+    }
+
+    let rules = actions
+        .iter()
+        .map(|action| action.rule())
+        .collect::<Vec<String>>();
+    let rule_text = syntax.layer_rule(rule_name, &rules, &literal_matcher(namespace));
+
+    write_rule_file(rule_name, &rule_text, output, None)
+}
@@ -0,0 +1,192 @@
+use hyprland::data::{Client, FullscreenMode};
+use regex::Regex;
+use std::collections::HashMap;
+
+use super::syntax::v2_field;
+use super::{ClientExtras, SelectWindowBy};
+
+/// Check a single `field:pattern` selector against a client the way Hyprland does: patterns
+/// must match the whole value and a `negative:` prefix inverts the match. Tag, xdgTag and
+/// content are read from `extras`, fields the client data from IPC doesn't carry never match.
+pub fn field_matches(field: &str, pattern: &str, client: &Client, extras: &ClientExtras) -> bool {
+    let value = match field {
+        "class" => client.class.clone(),
+        "title" => client.title.clone(),
+        "initialClass" => client.initial_class.clone(),
+        "initialTitle" => client.initial_title.clone(),
+        "xdgTag" => extras.xdg_tag.clone(),
+        "xwayland" => bool_value(client.xwayland),
+        "floating" => bool_value(client.floating),
+        "fullscreen" => bool_value(client.fullscreen != FullscreenMode::None),
+        "pinned" => bool_value(client.pinned),
+        "focus" => bool_value(client.focus_history_id == 0),
+        "group" => bool_value(!client.grouped.is_empty()),
+        "content" => extras.content_type.clone(),
+        "workspace" | "onworkspace" => {
+            return match pattern.strip_prefix("name:") {
+                Some(name) => client.workspace.name == name,
+                None => client.workspace.id.to_string() == pattern,
+            };
+        }
+        "tag" => {
+            return extras.tag_names().any(|tag| tag == pattern);
+        }
+        "fullscreenstate" => {
+            let states = [client.fullscreen as u8, client.fullscreen_client as u8];
+            let patterns = pattern.split_whitespace().collect::<Vec<&str>>();
+            return patterns.len() == 2
+                && patterns
+                    .iter()
+                    .zip(states)
+                    .all(|(pattern, state)| *pattern == "*" || *pattern == state.to_string());
+        }
+        _ => return false,
+    };
+    match field {
+        "xwayland" | "floating" | "fullscreen" | "pinned" | "focus" | "group" | "content" => {
+            value == pattern
+        }
+        _ => pattern_matches(pattern, &value),
+    }
+}
+
+/// The clients a rule with `matchers` would apply to, wherever they are: `clients` should come
+/// straight from `Clients::get()`, which includes windows on inactive and special workspaces,
+/// and `extras` from [`super::client_extras`].
+pub fn matching_clients<'c>(
+    matchers: &[(SelectWindowBy, String)],
+    clients: &'c [Client],
+    extras: &HashMap<String, ClientExtras>,
+) -> Vec<&'c Client> {
+    clients
+        .iter()
+        .filter(|client| {
+            let default_extras = ClientExtras::default();
+            let client_extras = extras
+                .get(&client.address.to_string())
+                .unwrap_or(&default_extras);
+            !matchers.is_empty()
+                && matchers.iter().all(|(select_by, pattern)| {
+                    field_matches(v2_field(select_by), pattern, client, client_extras)
+                })
+        })
+        .collect()
+}
+
+pub fn pattern_matches(pattern: &str, value: &str) -> bool {
+    let (negative, pattern) = match pattern.strip_prefix("negative:") {
+        Some(pattern) => (true, pattern),
+        None => (false, pattern),
+    };
+    match Regex::new(&format!("^(?:{pattern})$")) {
+        Ok(regex) => regex.is_match(value) != negative,
+        Err(_) => false,
+    }
+}
+
+pub(super) fn bool_value(value: bool) -> String {
+    match value {
+        true => "1".to_string(),
+        false => "0".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyprland::data::FullscreenMode;
+
+    use super::{field_matches, pattern_matches};
+    use crate::hyprland_config::ClientExtras;
+    use crate::test_client::client;
+
+    #[test]
+    fn patterns_match_the_whole_value() {
+        assert!(pattern_matches("kitty", "kitty"));
+        assert!(pattern_matches("kit.*", "kitty"));
+        assert!(!pattern_matches("kit", "kitty"));
+        assert!(!pattern_matches("itt", "kitty"));
+        // The anchors wrap the alternation as a whole, not just its ends
+        assert!(pattern_matches("foot|kitty", "kitty"));
+        assert!(!pattern_matches("foot|kitty", "kitty2"));
+        assert!(!pattern_matches("foot|kitty", "afoot"));
+        assert!(pattern_matches("^(kitty)$", "kitty"));
+    }
+
+    #[test]
+    fn negative_inverts_the_match() {
+        assert!(pattern_matches("negative:kitty", "foot"));
+        assert!(!pattern_matches("negative:kitty", "kitty"));
+        assert!(!pattern_matches("negative:kit.*", "kitty"));
+        // Still a whole-value match underneath
+        assert!(pattern_matches("negative:kit", "kitty"));
+    }
+
+    #[test]
+    fn invalid_patterns_match_nothing() {
+        assert!(!pattern_matches("(unbalanced", "(unbalanced"));
+        assert!(!pattern_matches("[a-", "a"));
+        // Not even inverted
+        assert!(!pattern_matches("negative:(unbalanced", "kitty"));
+    }
+
+    #[test]
+    fn workspace_matches_the_id_or_a_name() {
+        let mut window = client("0x1", "kitty", "notes");
+        window.workspace.id = 3;
+        window.workspace.name = "web".to_string();
+        let extras = ClientExtras::default();
+        for field in ["workspace", "onworkspace"] {
+            assert!(field_matches(field, "3", &window, &extras));
+            assert!(!field_matches(field, "4", &window, &extras));
+            assert!(field_matches(field, "name:web", &window, &extras));
+            assert!(!field_matches(field, "name:we", &window, &extras));
+            // The name is compared as is, not as a pattern
+            assert!(!field_matches(field, "name:w.*", &window, &extras));
+        }
+    }
+
+    #[test]
+    fn fullscreenstate_takes_two_states_or_wildcards() {
+        let mut window = client("0x1", "kitty", "notes");
+        window.fullscreen = FullscreenMode::Fullscreen;
+        window.fullscreen_client = FullscreenMode::Maximized;
+        let extras = ClientExtras::default();
+        for (pattern, expected) in [
+            ("2 1", true),
+            ("* 1", true),
+            ("2 *", true),
+            ("* *", true),
+            ("0 *", false),
+            ("2 2", false),
+            ("2", false),
+            ("2 1 0", false),
+        ] {
+            assert_eq!(
+                field_matches("fullscreenstate", pattern, &window, &extras),
+                expected,
+                "{pattern}"
+            );
+        }
+    }
+
+    #[test]
+    fn fields_read_the_client_and_its_extras() {
+        let mut window = client("0x1", "kitty", "notes");
+        window.floating = true;
+        let extras = ClientExtras {
+            tags: vec!["games*".to_string(), "work".to_string()],
+            xdg_tag: "dialog".to_string(),
+            content_type: "video".to_string(),
+        };
+        assert!(field_matches("class", "negative:foot", &window, &extras));
+        assert!(field_matches("title", "no.*", &window, &extras));
+        assert!(field_matches("floating", "1", &window, &extras));
+        assert!(!field_matches("pinned", "1", &window, &extras));
+        // Dynamic tags are matched without their `*`
+        assert!(field_matches("tag", "games", &window, &extras));
+        assert!(!field_matches("tag", "gam", &window, &extras));
+        assert!(field_matches("xdgTag", "dia.*", &window, &extras));
+        assert!(field_matches("content", "video", &window, &extras));
+        assert!(!field_matches("nosuchfield", ".*", &window, &extras));
+    }
+}
@@ -0,0 +1,535 @@
+use color_eyre::{Result, eyre};
+use hyprland::data::{Client, FullscreenMode};
+use hyprlang::Hyprland;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::system_info::get_window_rules_dir;
+use escape::literal_alternation;
+use matching::bool_value;
+
+mod apply;
+mod client_query;
+mod conflict;
+mod diff;
+mod escape;
+mod geometry;
+mod ipc;
+mod journal;
+mod layer_rule;
+mod matching;
+mod naming;
+mod rule_edit;
+mod rule_file;
+mod source_check;
+mod syntax;
+mod workspace_rule;
+
+pub use apply::{ApplyMode, apply_rule_file};
+pub use client_query::{ClientQuery, find_client, parse_match};
+pub use conflict::{ConflictKind, ConflictResolution, RuleConflict, find_conflicts};
+pub use diff::line_diff;
+pub use escape::literal_matcher;
+pub use geometry::{GeometryUnits, geometry_actions};
+pub use ipc::{ClientExtras, client_extras, extras_for};
+pub use journal::{ChangeKind, JournalEntry, read_journal, remove_file, truncate_journal};
+pub use layer_rule::{
+    LayerAction, LayerSurface, create_layer_rule_config, default_layer_rule_name, layer_surfaces,
+};
+pub use matching::matching_clients;
+pub use naming::{
+    default_rule_name, free_rule_name, hashed_rule_name, rule_file_path, validate_rule_name,
+};
+pub use rule_edit::{edit_rule_file, preview_rule_edit};
+pub use rule_file::{ParsedRule, RuleFile, read_rule_files};
+pub use source_check::{SourceCheck, SourcePaths, check_rules_sourced, insert_rules_source};
+pub use syntax::RuleSyntax;
+pub use workspace_rule::{
+    WorkspaceRuleOptions, create_workspace_rule_config, default_workspace_rule_name,
+    workspace_rule_text, workspace_selector,
+};
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum SelectWindowBy {
+    Title,
+    Class,
+    InitialClass,
+    InitialTitle,
+    XWayland,
+    Floating,
+    Fullscreen,
+    Pinned,
+    /// Whether the window has focus
+    Focus,
+    /// Whether the window is in a group
+    Group,
+    /// The workspace the window is on
+    Workspace,
+    /// A workspace selector the window's workspace has to match, e.g. `r[1-3]`
+    OnWorkspace,
+    Tag,
+    XdgTag,
+    /// The content type hint, e.g. `video`
+    Content,
+    /// The internal and client fullscreen modes, e.g. `2 0`
+    FullscreenState,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum WindowPlacement {
+    Float,
+    Tile,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum IdleInhibitMode {
+    None,
+    Always,
+    Focus,
+    Fullscreen,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum SuppressEvent {
+    Fullscreen,
+    Maximize,
+    Activate,
+    ActivateFocus,
+    FullscreenOutput,
+}
+
+/// Every rule action besides float/tile, named after the Hyprland keyword it produces.
+/// Values that Hyprland accepts as expressions (sizes, positions, opacity) are kept as
+/// strings and passed through untouched.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum WindowAction {
+    Fullscreen,
+    Maximize,
+    Pseudo,
+    Size(String),
+    MinSize(String),
+    MaxSize(String),
+    Move(String),
+    Center,
+    Pin,
+    Workspace {
+        workspace: String,
+        silent: bool,
+    },
+    Monitor(String),
+    Opacity(String),
+    NoBlur,
+    NoShadow,
+    NoBorder,
+    NoAnim,
+    NoDim,
+    NoFocus,
+    NoInitialFocus,
+    DimAround,
+    KeepAspectRatio,
+    Rounding(u32),
+    BorderSize(u32),
+    IdleInhibit(IdleInhibitMode),
+    SuppressEvent(Vec<SuppressEvent>),
+    StayFocused,
+    /// `+name` adds a tag and `-name` removes it
+    Tag(String),
+}
+
+/// Where [`create_window_rule_config`] puts the rule it generates.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum RuleOutput {
+    /// Write the rule file into the window rules directory
+    File,
+    /// Print the target path and the exact file contents without writing anything
+    DryRun,
+    /// Print only the rule text, for piping into other tools
+    Stdout,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct WindowOptions {
+    pub window_placement: Option<WindowPlacement>,
+    pub actions: Vec<WindowAction>,
+}
+
+impl SelectWindowBy {
+    /// The client's value for this property, `None` when it has none to match on: no tag or
+    /// no xdg tag. Tag, xdg tag and content come from `extras`.
+    pub fn client_value(&self, client: &Client, extras: &ClientExtras) -> Option<String> {
+        let value = match self {
+            SelectWindowBy::Title => client.title.clone(),
+            SelectWindowBy::Class => client.class.clone(),
+            SelectWindowBy::InitialClass => client.initial_class.clone(),
+            SelectWindowBy::InitialTitle => client.initial_title.clone(),
+            SelectWindowBy::XWayland => bool_value(client.xwayland),
+            SelectWindowBy::Floating => bool_value(client.floating),
+            SelectWindowBy::Fullscreen => bool_value(client.fullscreen != FullscreenMode::None),
+            SelectWindowBy::Pinned => bool_value(client.pinned),
+            SelectWindowBy::Focus => bool_value(client.focus_history_id == 0),
+            SelectWindowBy::Group => bool_value(!client.grouped.is_empty()),
+            SelectWindowBy::Workspace | SelectWindowBy::OnWorkspace => {
+                match client.workspace.name == client.workspace.id.to_string() {
+                    true => client.workspace.id.to_string(),
+                    false => format!("name:{}", client.workspace.name),
+                }
+            }
+            SelectWindowBy::Tag => extras.tag_names().next()?.to_string(),
+            SelectWindowBy::XdgTag => extras.xdg_tag.clone(),
+            SelectWindowBy::Content => extras.content_type.clone(),
+            SelectWindowBy::FullscreenState => format!(
+                "{} {}",
+                client.fullscreen as u8, client.fullscreen_client as u8
+            ),
+        };
+        Some(value).filter(|value| !value.is_empty())
+    }
+
+    /// Whether Hyprland reads this property's pattern as a regex, the rest take plain values.
+    pub fn is_regex(&self) -> bool {
+        matches!(
+            self,
+            SelectWindowBy::Title
+                | SelectWindowBy::Class
+                | SelectWindowBy::InitialClass
+                | SelectWindowBy::InitialTitle
+                | SelectWindowBy::XdgTag
+        )
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SelectWindowBy::Title => "Title",
+            SelectWindowBy::Class => "Class",
+            SelectWindowBy::InitialClass => "Initial Class",
+            SelectWindowBy::InitialTitle => "Initial Title",
+            SelectWindowBy::XWayland => "XWayland",
+            SelectWindowBy::Floating => "Floating",
+            SelectWindowBy::Fullscreen => "Fullscreen",
+            SelectWindowBy::Pinned => "Pinned",
+            SelectWindowBy::Focus => "Focused",
+            SelectWindowBy::Group => "Grouped",
+            SelectWindowBy::Workspace => "Workspace",
+            SelectWindowBy::OnWorkspace => "On Workspace",
+            SelectWindowBy::Tag => "Tag",
+            SelectWindowBy::XdgTag => "XDG Tag",
+            SelectWindowBy::Content => "Content Type",
+            SelectWindowBy::FullscreenState => "Fullscreen State",
+        }
+    }
+
+    /// Every property, in the order the pickers list them.
+    pub fn all() -> [SelectWindowBy; 16] {
+        [
+            SelectWindowBy::Title,
+            SelectWindowBy::Class,
+            SelectWindowBy::InitialTitle,
+            SelectWindowBy::InitialClass,
+            SelectWindowBy::XWayland,
+            SelectWindowBy::Floating,
+            SelectWindowBy::Fullscreen,
+            SelectWindowBy::Pinned,
+            SelectWindowBy::Focus,
+            SelectWindowBy::Group,
+            SelectWindowBy::Workspace,
+            SelectWindowBy::OnWorkspace,
+            SelectWindowBy::Tag,
+            SelectWindowBy::XdgTag,
+            SelectWindowBy::Content,
+            SelectWindowBy::FullscreenState,
+        ]
+    }
+}
+
+impl WindowPlacement {
+    pub fn rule(&self) -> String {
+        match self {
+            WindowPlacement::Float => "float",
+            WindowPlacement::Tile => "tile",
+        }
+        .to_string()
+    }
+}
+
+impl IdleInhibitMode {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "none" => Some(IdleInhibitMode::None),
+            "always" => Some(IdleInhibitMode::Always),
+            "focus" => Some(IdleInhibitMode::Focus),
+            "fullscreen" => Some(IdleInhibitMode::Fullscreen),
+            _ => None,
+        }
+    }
+
+    fn keyword(&self) -> &'static str {
+        match self {
+            IdleInhibitMode::None => "none",
+            IdleInhibitMode::Always => "always",
+            IdleInhibitMode::Focus => "focus",
+            IdleInhibitMode::Fullscreen => "fullscreen",
+        }
+    }
+}
+
+impl SuppressEvent {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "fullscreen" => Some(SuppressEvent::Fullscreen),
+            "maximize" => Some(SuppressEvent::Maximize),
+            "activate" => Some(SuppressEvent::Activate),
+            "activatefocus" => Some(SuppressEvent::ActivateFocus),
+            "fullscreenoutput" => Some(SuppressEvent::FullscreenOutput),
+            _ => None,
+        }
+    }
+
+    fn keyword(&self) -> &'static str {
+        match self {
+            SuppressEvent::Fullscreen => "fullscreen",
+            SuppressEvent::Maximize => "maximize",
+            SuppressEvent::Activate => "activate",
+            SuppressEvent::ActivateFocus => "activatefocus",
+            SuppressEvent::FullscreenOutput => "fullscreenoutput",
+        }
+    }
+}
+
+impl WindowAction {
+    /// The inverse of [`WindowAction::rule`], `None` for float/tile and anything unknown.
+    pub fn from_rule(rule: &str) -> Option<Self> {
+        let rule = rule.trim();
+        let (keyword, value) = match rule.split_once(char::is_whitespace) {
+            Some((keyword, value)) => (keyword, Some(value.trim())),
+            None => (rule, None),
+        };
+        let action = match (keyword, value) {
+            ("fullscreen", None) => WindowAction::Fullscreen,
+            ("maximize", None) => WindowAction::Maximize,
+            ("pseudo", None) => WindowAction::Pseudo,
+            ("size", Some(size)) => WindowAction::Size(size.to_string()),
+            ("minsize", Some(size)) => WindowAction::MinSize(size.to_string()),
+            ("maxsize", Some(size)) => WindowAction::MaxSize(size.to_string()),
+            ("move", Some(position)) => WindowAction::Move(position.to_string()),
+            ("center", _) => WindowAction::Center,
+            ("pin", None) => WindowAction::Pin,
+            ("workspace", Some(workspace)) => match workspace.strip_suffix("silent") {
+                Some(workspace) => WindowAction::Workspace {
+                    workspace: workspace.trim().to_string(),
+                    silent: true,
+                },
+                None => WindowAction::Workspace {
+                    workspace: workspace.to_string(),
+                    silent: false,
+                },
+            },
+            ("monitor", Some(monitor)) => WindowAction::Monitor(monitor.to_string()),
+            ("opacity", Some(opacity)) => WindowAction::Opacity(opacity.to_string()),
+            ("noblur", None) => WindowAction::NoBlur,
+            ("noshadow", None) => WindowAction::NoShadow,
+            ("noborder", None) => WindowAction::NoBorder,
+            ("noanim", None) => WindowAction::NoAnim,
+            ("nodim", None) => WindowAction::NoDim,
+            ("nofocus", None) => WindowAction::NoFocus,
+            ("noinitialfocus", None) => WindowAction::NoInitialFocus,
+            ("dimaround", None) => WindowAction::DimAround,
+            ("keepaspectratio", None) => WindowAction::KeepAspectRatio,
+            ("rounding", Some(rounding)) => WindowAction::Rounding(rounding.parse().ok()?),
+            ("bordersize", Some(border_size)) => {
+                WindowAction::BorderSize(border_size.parse().ok()?)
+            }
+            ("idleinhibit", Some(mode)) => {
+                WindowAction::IdleInhibit(IdleInhibitMode::from_keyword(mode)?)
+            }
+            ("suppressevent", Some(events)) => WindowAction::SuppressEvent(
+                events
+                    .split_whitespace()
+                    .map(SuppressEvent::from_keyword)
+                    .collect::<Option<Vec<SuppressEvent>>>()?,
+            ),
+            ("stayfocused", None) => WindowAction::StayFocused,
+            // A bare name toggles the tag, which for a window that just opened is adding it
+            ("tag", Some(tag)) if !tag.contains([' ', ',']) => match tag.starts_with(['+', '-']) {
+                true => WindowAction::Tag(tag.to_string()),
+                false => WindowAction::Tag(format!("+{tag}")),
+            },
+            _ => return None,
+        };
+        Some(action)
+    }
+
+    pub fn rule(&self) -> String {
+        match self {
+            WindowAction::Fullscreen => "fullscreen".to_string(),
+            WindowAction::Maximize => "maximize".to_string(),
+            WindowAction::Pseudo => "pseudo".to_string(),
+            WindowAction::Size(size) => format!("size {size}"),
+            WindowAction::MinSize(size) => format!("minsize {size}"),
+            WindowAction::MaxSize(size) => format!("maxsize {size}"),
+            WindowAction::Move(position) => format!("move {position}"),
+            WindowAction::Center => "center".to_string(),
+            WindowAction::Pin => "pin".to_string(),
+            WindowAction::Workspace { workspace, silent } => match silent {
+                true => format!("workspace {workspace} silent"),
+                false => format!("workspace {workspace}"),
+            },
+            WindowAction::Monitor(monitor) => format!("monitor {monitor}"),
+            WindowAction::Opacity(opacity) => format!("opacity {opacity}"),
+            WindowAction::NoBlur => "noblur".to_string(),
+            WindowAction::NoShadow => "noshadow".to_string(),
+            WindowAction::NoBorder => "noborder".to_string(),
+            WindowAction::NoAnim => "noanim".to_string(),
+            WindowAction::NoDim => "nodim".to_string(),
+            WindowAction::NoFocus => "nofocus".to_string(),
+            WindowAction::NoInitialFocus => "noinitialfocus".to_string(),
+            WindowAction::DimAround => "dimaround".to_string(),
+            WindowAction::KeepAspectRatio => "keepaspectratio".to_string(),
+            WindowAction::Rounding(rounding) => format!("rounding {rounding}"),
+            WindowAction::BorderSize(border_size) => format!("bordersize {border_size}"),
+            WindowAction::IdleInhibit(mode) => format!("idleinhibit {}", mode.keyword()),
+            WindowAction::SuppressEvent(events) => format!(
+                "suppressevent {}",
+                events
+                    .iter()
+                    .map(|event| event.keyword())
+                    .collect::<Vec<&str>>()
+                    .join(" ")
+            ),
+            WindowAction::StayFocused => "stayfocused".to_string(),
+            WindowAction::Tag(tag) => format!("tag {tag}"),
+        }
+    }
+}
+
+impl WindowOptions {
+    /// The action half of every rule line, placement first.
+    pub fn rules(&self) -> Vec<String> {
+        self.window_placement
+            .iter()
+            .map(|placement| placement.rule())
+            .chain(self.actions.iter().map(|action| action.rule()))
+            .collect()
+    }
+
+    /// The reverse of [`Self::rules`], for actions written out by hand.
+    pub fn from_rules(rules: &[String]) -> Result<Self> {
+        let mut window_options = WindowOptions {
+            window_placement: None,
+            actions: vec![],
+        };
+        for rule in rules {
+            match rule.trim() {
+                "float" => window_options.window_placement = Some(WindowPlacement::Float),
+                "tile" => window_options.window_placement = Some(WindowPlacement::Tile),
+                rule => window_options.actions.push(
+                    WindowAction::from_rule(rule)
+                        .ok_or_else(|| eyre::eyre!("\"{rule}\" isn't a window rule action"))?,
+                ),
+            }
+        }
+        Ok(window_options)
+    }
+}
+
+/// Literal patterns for the picked properties of `client`. Fails when the client has no value
+/// for one of them, e.g. an untagged window picked by tag.
+pub fn client_matchers(
+    client: &Client,
+    extras: &ClientExtras,
+    select_by_list: &[SelectWindowBy],
+) -> Result<Vec<(SelectWindowBy, String)>> {
+    combined_matchers(&[(client, extras)], select_by_list)
+}
+
+/// Patterns for one rule covering all of `clients`. Text properties match any of the clients'
+/// values, the others have to be the same for every client.
+pub fn combined_matchers(
+    clients: &[(&Client, &ClientExtras)],
+    select_by_list: &[SelectWindowBy],
+) -> Result<Vec<(SelectWindowBy, String)>> {
+    select_by_list
+        .iter()
+        .map(|select_by| {
+            let mut values: Vec<String> = vec![];
+            for (client, extras) in clients {
+                let value = select_by.client_value(client, extras).ok_or_else(|| {
+                    eyre::eyre!(
+                        "{} has no {} to match on",
+                        client.class,
+                        select_by.label().to_lowercase()
+                    )
+                })?;
+                if !values.contains(&value) {
+                    values.push(value);
+                }
+            }
+            let pattern = match (select_by.is_regex(), values.as_slice()) {
+                (true, values) => literal_alternation(values),
+                (false, [value]) => value.clone(),
+                (false, _) => {
+                    return Err(eyre::eyre!(
+                        "The windows don't share a {} value, leave it out of the selector or \
+                         write one rule per window",
+                        select_by.label().to_lowercase()
+                    ));
+                }
+            };
+            Ok((select_by.clone(), pattern))
+        })
+        .collect()
+}
+
+/// Write a rule matching `matchers` into `{rule_name}.conf`, see [`default_rule_name`] for the
+/// name used when the user doesn't pick one. `client` is the window the rule was made for, it
+/// goes into the journal.
+pub fn create_window_rule_config(
+    cli_options: &WindowOptions,
+    matchers: &[(SelectWindowBy, String)],
+    rule_name: &str,
+    syntax: RuleSyntax,
+    output: RuleOutput,
+    client: Option<&Client>,
+) -> Result<PathBuf> {
+    let rule_text = syntax.window_rule(rule_name, &cli_options.rules(), matchers)?;
+
+    write_rule_file(rule_name, &rule_text, output, client)
+}
+
+/// Write rendered rule text into `{rule_name}.conf` in the window rules directory, or print it
+/// for the dry-run and stdout outputs. Shared by every kind of rule this tool generates.
+fn write_rule_file(
+    rule_name: &str,
+    rule_text: &str,
+    output: RuleOutput,
+    client: Option<&Client>,
+) -> Result<PathBuf> {
+    // Parsing the rendered text back through hyprlang catches anything the backends got wrong
+    // before it ends up in a file Hyprland will try to load.
+    let mut hypr = Hyprland::new();
+    hypr.parse(rule_text)?;
+    let file_contents = hypr.config().serialize();
+
+    let window_rules_path = get_window_rules_dir()?;
+    let file_path = Path::join(&window_rules_path, format!("{rule_name}.conf"));
+    match output {
+        RuleOutput::File => {
+            if !fs::exists(&window_rules_path)? {
+                fs::create_dir_all(&window_rules_path)?;
+            }
+            println!("Writing to {}", file_path.to_string_lossy());
+            for line in rule_text.lines() {
+                println!("    {line}");
+            }
+            journal::write_file(&file_path, &file_contents, client)?;
+        }
+        RuleOutput::DryRun => {
+            println!("Would write to {}", file_path.to_string_lossy());
+            print!("{file_contents}");
+        }
+        RuleOutput::Stdout => print!("{rule_text}"),
+    }
+
+    Ok(file_path)
+}
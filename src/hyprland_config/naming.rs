@@ -0,0 +1,164 @@
+use color_eyre::{Result, eyre};
+use std::{fs, path::PathBuf};
+
+use super::SelectWindowBy;
+use super::syntax::v2_field;
+use crate::system_info::get_window_rules_dir;
+
+/// Longest class prefix kept in a generated name, the hash keeps it unique.
+const MAX_CLASS_LENGTH: usize = 24;
+
+/// `{class}-{hash}`, where the hash covers the whole selector so two rules for the same class
+/// but different titles get different files, and the same selector always gets the same name.
+/// `class` is the picked window's class, or whatever else names a rule without a window.
+pub fn default_rule_name(class: &str, matchers: &[(SelectWindowBy, String)]) -> String {
+    let mut selector: Vec<String> = matchers
+        .iter()
+        .map(|(select_by, pattern)| format!("{}:{pattern}", v2_field(select_by)))
+        .collect();
+    selector.sort();
+    hashed_rule_name(class, &selector.join(","))
+}
+
+/// `{base}-{hash}` for rules that aren't about a window, `key` being whatever tells two of
+/// them apart, e.g. the workspace selector of a workspace rule.
+pub fn hashed_rule_name(base: &str, key: &str) -> String {
+    let class: String = base
+        .to_lowercase()
+        .chars()
+        .map(|character| match character.is_ascii_alphanumeric() {
+            true => character,
+            false => '-',
+        })
+        .collect();
+    let class = class
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-");
+    let class = match class.is_empty() {
+        true => "window".to_string(),
+        false => class.chars().take(MAX_CLASS_LENGTH).collect(),
+    };
+    format!("{class}-{:08x}", fnv1a(key.as_bytes()) as u32)
+}
+
+/// Check a name given on the command line, it becomes both the file name and the block rule's
+/// name so it's kept to characters that are safe in either place.
+pub fn validate_rule_name(name: &str) -> Result<String> {
+    let name = name.strip_suffix(".conf").unwrap_or(name);
+    let is_valid = !name.is_empty()
+        && !name.starts_with(['.', '-'])
+        && name
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || "-_.".contains(character));
+    match is_valid {
+        true => Ok(name.to_string()),
+        false => Err(eyre::eyre!(
+            "\"{name}\" can't be used as a rule name, stick to letters, digits, '-', '_' and '.'"
+        )),
+    }
+}
+
+/// Where a rule called `rule_name` is written.
+pub fn rule_file_path(rule_name: &str) -> Result<PathBuf> {
+    Ok(get_window_rules_dir()?.join(format!("{rule_name}.conf")))
+}
+
+/// The first of `rule_name-2`, `rule_name-3`, ... that has no file yet.
+pub fn free_rule_name(rule_name: &str) -> Result<String> {
+    for suffix in 2.. {
+        let candidate = format!("{rule_name}-{suffix}");
+        if !fs::exists(rule_file_path(&candidate)?)? {
+            return Ok(candidate);
+        }
+    }
+    unreachable!("ran out of suffixes for {rule_name}")
+}
+
+/// 64-bit FNV-1a, unlike `DefaultHasher` it is guaranteed to give the same names across
+/// builds.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{default_rule_name, validate_rule_name};
+    use crate::hyprland_config::SelectWindowBy;
+
+    fn firefox(title: &str) -> Vec<(SelectWindowBy, String)> {
+        vec![
+            (SelectWindowBy::Class, "^(firefox)$".to_string()),
+            (SelectWindowBy::Title, format!("^({title})$")),
+        ]
+    }
+
+    #[test]
+    fn the_same_selector_always_gets_the_same_name() {
+        let name = default_rule_name("firefox", &firefox("Library"));
+        // FNV-1a doesn't change between builds, so neither do the names already on disk
+        assert_eq!(name, "firefox-f91f998f");
+        let mut reordered = firefox("Library");
+        reordered.reverse();
+        assert_eq!(default_rule_name("firefox", &reordered), name);
+    }
+
+    #[test]
+    fn different_selectors_get_different_names() {
+        assert_ne!(
+            default_rule_name("firefox", &firefox("Library")),
+            default_rule_name("firefox", &firefox("Picture-in-Picture"))
+        );
+        assert_ne!(
+            default_rule_name("firefox", &firefox("Library")),
+            default_rule_name(
+                "firefox",
+                &[(SelectWindowBy::Class, "^(firefox)$".to_string())]
+            )
+        );
+    }
+
+    #[test]
+    fn classes_without_ascii_become_a_readable_prefix() {
+        let matchers = firefox("Library");
+        let hash = |name: String| name.rsplit_once('-').unwrap().1.to_string();
+        let expected_hash = hash(default_rule_name("firefox", &matchers));
+        for (class, prefix) in [
+            ("", "window"),
+            ("日本語", "window"),
+            ("---", "window"),
+            ("Org.Gnome.Nautilus", "org-gnome-nautilus"),
+            ("Café Crème", "caf-cr-me"),
+            ("  kitty  ", "kitty"),
+        ] {
+            let name = default_rule_name(class, &matchers);
+            assert_eq!(name, format!("{prefix}-{expected_hash}"), "{class:?}");
+        }
+        let long = default_rule_name(&"a".repeat(40), &matchers);
+        assert_eq!(long, format!("{}-{expected_hash}", "a".repeat(24)));
+    }
+
+    #[test]
+    fn given_names_stay_inside_the_rules_directory() {
+        assert_eq!(validate_rule_name("kitty-float").unwrap(), "kitty-float");
+        assert_eq!(validate_rule_name("kitty_v2.conf").unwrap(), "kitty_v2");
+        for name in [
+            "",
+            ".conf",
+            "..",
+            "../kitty",
+            "rules/kitty",
+            "/etc/kitty",
+            "..\\kitty",
+            ".hidden",
+            "-flag",
+            "kitty float",
+            "kätty",
+        ] {
+            assert!(validate_rule_name(name).is_err(), "{name:?}");
+        }
+    }
+}
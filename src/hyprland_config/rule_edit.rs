@@ -0,0 +1,349 @@
+use color_eyre::{Result, eyre};
+use hyprland::data::Client;
+use hyprlang::Hyprland;
+use std::fs;
+
+use super::journal;
+use super::rule_file::{ParsedRule, RuleFile, parse_keyword_rule, parsed_rules};
+use super::{RuleSyntax, SelectWindowBy};
+
+/// Replace `rule` in its file with one made of `rules` and `matchers`, keeping the rule's
+/// syntax and everything else in the file as it was.
+///
+/// hyprlang's serializer rebuilds the file from its parsed values and drops comments and blank
+/// lines on the way, so the new rule is spliced into the original text instead and the result
+/// is parsed back through hyprlang before anything is written. `client` is the window the
+/// edit was made for, it goes into the journal.
+pub fn edit_rule_file(
+    rule_file: &RuleFile,
+    rule: &ParsedRule,
+    rules: &[String],
+    matchers: &[(SelectWindowBy, String)],
+    client: Option<&Client>,
+) -> Result<()> {
+    let (rule_text, edited) = render_rule_edit(rule_file, rule, rules, matchers)?;
+
+    println!("Writing to {}", rule_file.path.to_string_lossy());
+    for line in rule_text.lines() {
+        println!("    {line}");
+    }
+    journal::write_file(&rule_file.path, &edited, client)?;
+
+    Ok(())
+}
+
+/// The contents `rule_file` would have after [`edit_rule_file`], without writing them.
+pub fn preview_rule_edit(
+    rule_file: &RuleFile,
+    rule: &ParsedRule,
+    rules: &[String],
+    matchers: &[(SelectWindowBy, String)],
+) -> Result<String> {
+    let (_, edited) = render_rule_edit(rule_file, rule, rules, matchers)?;
+    Ok(edited)
+}
+
+/// Render the new rule and splice it into the file's text, returning both.
+fn render_rule_edit(
+    rule_file: &RuleFile,
+    rule: &ParsedRule,
+    rules: &[String],
+    matchers: &[(SelectWindowBy, String)],
+) -> Result<(String, String)> {
+    let name = match &rule.name {
+        Some(name) => name.clone(),
+        None => rule_file
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    };
+    let rule_text = rule.syntax.window_rule(&name, rules, matchers)?;
+
+    let original = fs::read_to_string(&rule_file.path)?;
+    let edited = match rule.syntax {
+        RuleSyntax::Block => splice_block(&original, &name, &rule_text)?,
+        RuleSyntax::V1 | RuleSyntax::V2 => splice_keyword_lines(&original, rule, &rule_text),
+    };
+
+    let mut hypr = Hyprland::new();
+    hypr.parse(&edited)?;
+    let expected_selector = matchers
+        .iter()
+        .map(|(_, pattern)| pattern)
+        .collect::<Vec<&String>>();
+    let is_written = parsed_rules(&hypr)?.iter().any(|parsed| {
+        parsed.selector.len() == expected_selector.len()
+            && expected_selector
+                .iter()
+                .all(|pattern| parsed.selector.iter().any(|(_, parsed)| &parsed == pattern))
+    });
+    if !is_written {
+        return Err(eyre::eyre!(
+            "The edited rule could not be read back from {}",
+            rule_file.path.to_string_lossy()
+        ));
+    }
+
+    Ok((rule_text, edited))
+}
+
+/// Swap every `windowrule(v2) = ...` line belonging to `rule` for the new lines, written where
+/// the first of them was.
+fn splice_keyword_lines(original: &str, rule: &ParsedRule, rule_text: &str) -> String {
+    let newline = line_ending(original);
+    let rule_text = rule_text
+        .lines()
+        .map(|line| format!("{line}{newline}"))
+        .collect::<String>();
+    let mut edited = String::with_capacity(original.len());
+    let mut is_replaced = false;
+    for line in original.split_inclusive('\n') {
+        if !is_rule_line(line, rule) {
+            edited.push_str(line);
+        } else if !is_replaced {
+            edited.push_str(&rule_text);
+            is_replaced = true;
+        }
+    }
+    if !is_replaced {
+        edited.push_str(&rule_text);
+    }
+    edited
+}
+
+fn is_rule_line(line: &str, rule: &ParsedRule) -> bool {
+    let line = line.split('#').next().unwrap_or_default();
+    let Some((keyword, value)) = line.split_once('=') else {
+        return false;
+    };
+    let is_v2 = match keyword.trim() {
+        "windowrule" => false,
+        "windowrulev2" => true,
+        _ => return false,
+    };
+    parse_keyword_rule(value.trim(), is_v2)
+        .is_some_and(|parsed| parsed.syntax == rule.syntax && parsed.selector == rule.selector)
+}
+
+/// Update the `key = value` lines inside `windowrule[name] { ... }` in place: changed values are
+/// rewritten, keys the new rule no longer has are dropped and new keys go before the `}`.
+fn splice_block(original: &str, name: &str, rule_text: &str) -> Result<String> {
+    let newline = line_ending(original);
+    let mut new_values: Vec<(&str, &str, bool)> = rule_text
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim(), false))
+        .collect();
+
+    let header = format!("windowrule[{name}]");
+    let mut edited = String::with_capacity(original.len());
+    let mut lines = original.split_inclusive('\n');
+    let mut is_found = false;
+    for line in lines.by_ref() {
+        edited.push_str(line);
+        if line.trim_start().starts_with(&header) {
+            is_found = true;
+            break;
+        }
+    }
+    if !is_found {
+        return Err(eyre::eyre!("Unable to find {header} in the rule file"));
+    }
+
+    let mut indent = "  ";
+    for line in lines.by_ref() {
+        let content = line.split('#').next().unwrap_or_default();
+        if content.trim() == "}" {
+            for (key, value, is_written) in &new_values {
+                if !is_written {
+                    edited.push_str(&format!("{indent}{key} = {value}{newline}"));
+                }
+            }
+            edited.push_str(line);
+            break;
+        }
+        let Some((key, _)) = content.split_once('=') else {
+            edited.push_str(line);
+            continue;
+        };
+        indent = &line[..line.len() - line.trim_start().len()];
+        if let Some((key, value, is_written)) = new_values
+            .iter_mut()
+            .find(|(new_key, _, _)| *new_key == key.trim())
+        {
+            // A comment after the old value stays with the new one
+            let comment = match line.find('#') {
+                Some(start) => format!(" {}", line[start..].trim_end()),
+                None => String::new(),
+            };
+            edited.push_str(&format!("{indent}{key} = {value}{comment}{newline}"));
+            *is_written = true;
+        }
+    }
+    edited.extend(lines);
+
+    Ok(edited)
+}
+
+/// `\r\n` for a file written with Windows line endings, so spliced lines match the rest of it.
+fn line_ending(text: &str) -> &'static str {
+    match text.contains("\r\n") {
+        true => "\r\n",
+        false => "\n",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyprlang::Hyprland;
+
+    use super::{splice_block, splice_keyword_lines};
+    use crate::hyprland_config::rule_file::{ParsedRule, parse_keyword_rule, parsed_rules};
+
+    fn v2_rule(line: &str) -> ParsedRule {
+        parse_keyword_rule(line, true).unwrap()
+    }
+
+    fn assert_parses(text: &str) -> Vec<ParsedRule> {
+        let mut hypr = Hyprland::new();
+        hypr.parse(text)
+            .unwrap_or_else(|error| panic!("{text:?} didn't parse: {error}"));
+        parsed_rules(&hypr).unwrap()
+    }
+
+    #[test]
+    fn keyword_lines_sharing_a_selector_are_replaced_once_where_the_first_was() {
+        let original = "\
+# kitty rules
+windowrulev2 = float, class:^(kitty)$
+windowrulev2 = float, class:^(foot)$
+windowrulev2 = size 800 600, class:^(kitty)$ # keep it small
+windowrulev2 = center, class:^(kitty)$
+";
+        let rule = v2_rule("float, class:^(kitty)$");
+        let edited = splice_keyword_lines(
+            original,
+            &rule,
+            "windowrulev2 = tile, class:^(kitty)$\nwindowrulev2 = pin, class:^(kitty)$\n",
+        );
+        assert_eq!(
+            edited,
+            "\
+# kitty rules
+windowrulev2 = tile, class:^(kitty)$
+windowrulev2 = pin, class:^(kitty)$
+windowrulev2 = float, class:^(foot)$
+"
+        );
+        assert_eq!(assert_parses(&edited).len(), 2);
+    }
+
+    #[test]
+    fn keyword_lines_of_the_other_syntax_are_left_alone() {
+        let original = "windowrule = float, title:^(foo)$\nwindowrulev2 = float, title:^(foo)$\n";
+        let rule = v2_rule("float, title:^(foo)$");
+        let edited = splice_keyword_lines(original, &rule, "windowrulev2 = tile, title:^(foo)$\n");
+        assert_eq!(
+            edited,
+            "windowrule = float, title:^(foo)$\nwindowrulev2 = tile, title:^(foo)$\n"
+        );
+    }
+
+    #[test]
+    fn a_missing_keyword_rule_is_appended() {
+        let original = "# nothing here yet\n";
+        let rule = v2_rule("float, class:^(kitty)$");
+        let edited =
+            splice_keyword_lines(original, &rule, "windowrulev2 = tile, class:^(kitty)$\n");
+        assert_eq!(
+            edited,
+            "# nothing here yet\nwindowrulev2 = tile, class:^(kitty)$\n"
+        );
+    }
+
+    #[test]
+    fn keyword_lines_keep_crlf_line_endings() {
+        let original = "# kitty\r\nwindowrulev2 = float, class:^(kitty)$\r\n# end\r\n";
+        let rule = v2_rule("float, class:^(kitty)$");
+        let edited = splice_keyword_lines(
+            original,
+            &rule,
+            "windowrulev2 = tile, class:^(kitty)$\nwindowrulev2 = pin, class:^(kitty)$\n",
+        );
+        assert_eq!(
+            edited,
+            "# kitty\r\nwindowrulev2 = tile, class:^(kitty)$\r\n\
+             windowrulev2 = pin, class:^(kitty)$\r\n# end\r\n"
+        );
+    }
+
+    #[test]
+    fn a_block_is_updated_in_place_and_trailing_comments_survive() {
+        let original = "\
+windowrule[kitty] {
+    name = kitty
+    match:class = ^(kitty)$
+    float = on # floats
+    size = 800 600
+}
+# trailing comment
+
+windowrule[foot] {
+    name = foot
+    match:class = ^(foot)$
+    float = on
+}
+# the end
+";
+        let rule_text = "\
+windowrule[kitty] {
+  name = kitty
+  match:class = ^(kitty)$
+  float = off
+  center = on
+}
+";
+        let edited = splice_block(original, "kitty", rule_text).unwrap();
+        assert_eq!(
+            edited,
+            "\
+windowrule[kitty] {
+    name = kitty
+    match:class = ^(kitty)$
+    float = off # floats
+    center = on
+}
+# trailing comment
+
+windowrule[foot] {
+    name = foot
+    match:class = ^(foot)$
+    float = on
+}
+# the end
+"
+        );
+        assert_eq!(assert_parses(&edited).len(), 2);
+    }
+
+    #[test]
+    fn a_block_keeps_crlf_line_endings() {
+        let original = "windowrule[kitty] {\r\n  name = kitty\r\n  match:class = ^(kitty)$\r\n  \
+                        float = on\r\n}\r\n# after\r\n";
+        let rule_text = "windowrule[kitty] {\n  name = kitty\n  match:class = ^(kitty)$\n  \
+                         float = on\n  pin = on\n}\n";
+        let edited = splice_block(original, "kitty", rule_text).unwrap();
+        assert_eq!(
+            edited,
+            "windowrule[kitty] {\r\n  name = kitty\r\n  match:class = ^(kitty)$\r\n  \
+             float = on\r\n  pin = on\r\n}\r\n# after\r\n"
+        );
+    }
+
+    #[test]
+    fn a_missing_block_is_an_error() {
+        let original = "windowrule[foot] {\n  name = foot\n}\n";
+        assert!(splice_block(original, "kitty", "windowrule[kitty] {\n}\n").is_err());
+    }
+}
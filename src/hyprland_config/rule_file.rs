@@ -0,0 +1,301 @@
+use color_eyre::{Result, eyre::WrapErr};
+use hyprland::data::Client;
+use hyprlang::Hyprland;
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::matching::{field_matches, pattern_matches};
+use super::syntax::{select_by_from_v2_field, v2_field_from_block, v2_keyword_from_block};
+use super::{ClientExtras, RuleSyntax, SelectWindowBy};
+use crate::system_info::get_window_rules_dir;
+
+/// Fields Hyprland's windowrulev2 parser recognises in front of a `:`.
+const V2_FIELDS: [&str; 16] = [
+    "class",
+    "title",
+    "initialClass",
+    "initialTitle",
+    "tag",
+    "xwayland",
+    "floating",
+    "fullscreen",
+    "pinned",
+    "focus",
+    "group",
+    "fullscreenstate",
+    "workspace",
+    "onworkspace",
+    "content",
+    "xdgTag",
+];
+
+/// Boolean effects, written bare in the keyword syntaxes and as `effect = on` in blocks.
+const TOGGLE_ACTIONS: [&str; 17] = [
+    "float",
+    "tile",
+    "fullscreen",
+    "maximize",
+    "pseudo",
+    "center",
+    "pin",
+    "noblur",
+    "noshadow",
+    "noborder",
+    "noanim",
+    "nodim",
+    "nofocus",
+    "noinitialfocus",
+    "dimaround",
+    "keepaspectratio",
+    "stayfocused",
+];
+
+/// One rule read back from a file: everything sharing a selector is folded together, so the
+/// one-line-per-action keyword syntaxes and a single block both come out as one rule.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParsedRule {
+    pub syntax: RuleSyntax,
+    /// Block rules carry a name, keyword rules don't
+    pub name: Option<String>,
+    /// `(field, pattern)` pairs using the windowrulev2 field names
+    pub selector: Vec<(String, String)>,
+    /// Actions in the windowrulev2 spelling, e.g. `size 800 600`
+    pub actions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleFile {
+    pub path: PathBuf,
+    pub rules: Vec<ParsedRule>,
+}
+
+impl ParsedRule {
+    /// Whether the rule applies to `client`. Tag, xdgTag and content selectors are checked
+    /// against `extras`, from [`super::client_extras`].
+    pub fn matches_with(&self, client: &Client, extras: &ClientExtras) -> bool {
+        !self.selector.is_empty()
+            && self
+                .selector
+                .iter()
+                .all(|(field, pattern)| field_matches(field, pattern, client, extras))
+    }
+
+    /// Whether every `(field, value)` filter has a selector on that field matching the value,
+    /// e.g. `[("class", "kitty")]` picks out rules that would apply to kitty windows.
+    pub fn matches_values(&self, filters: &[(&str, &str)]) -> bool {
+        filters.iter().all(|(filter_field, value)| {
+            self.selector
+                .iter()
+                .any(|(field, pattern)| field == filter_field && pattern_matches(pattern, value))
+        })
+    }
+
+    /// The selector as [`SelectWindowBy`] pairs, `None` if it uses any other field.
+    pub fn matchers(&self) -> Option<Vec<(SelectWindowBy, String)>> {
+        self.selector
+            .iter()
+            .map(|(field, pattern)| Some((select_by_from_v2_field(field)?, pattern.clone())))
+            .collect()
+    }
+
+    /// `class:^(kitty)$ title:^(foo)$`
+    pub fn selector_text(&self) -> String {
+        self.selector
+            .iter()
+            .map(|(field, pattern)| format!("{field}:{pattern}"))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+impl RuleFile {
+    /// Match `kitty-foo.conf` or just `kitty-foo` against this file.
+    pub fn has_name(&self, name: &str) -> bool {
+        self.file_name() == name
+            || self
+                .path
+                .file_stem()
+                .is_some_and(|stem| stem.to_string_lossy() == name)
+    }
+
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Parse every `.conf` file in the managed rules directory, sorted by file name. Dotfiles such
+/// as editor swap files are passed over, and a file that doesn't parse is skipped with a
+/// warning so one broken file doesn't stop every command that reads the rules.
+pub fn read_rule_files() -> Result<Vec<RuleFile>> {
+    let window_rules_path = get_window_rules_dir()?;
+    if !fs::exists(&window_rules_path)? {
+        return Ok(vec![]);
+    }
+
+    let mut paths = fs::read_dir(&window_rules_path)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_rule_file_name(path))
+        .collect::<Vec<PathBuf>>();
+    paths.sort();
+
+    Ok(paths
+        .iter()
+        .filter_map(|path| match read_rule_file(path) {
+            Ok(rule_file) => Some(rule_file),
+            // Stderr, so `list --json` stays parseable
+            Err(error) => {
+                eprintln!("Skipping {error:#}");
+                None
+            }
+        })
+        .collect())
+}
+
+fn is_rule_file_name(path: &Path) -> bool {
+    let is_hidden = path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+    !is_hidden
+        && path
+            .extension()
+            .is_some_and(|extension| extension == "conf")
+}
+
+pub fn read_rule_file(path: &Path) -> Result<RuleFile> {
+    let mut hypr = Hyprland::new();
+    hypr.parse_file(path)
+        .wrap_err_with(|| format!("Failed to parse {}", path.to_string_lossy()))?;
+
+    Ok(RuleFile {
+        path: path.to_path_buf(),
+        rules: parsed_rules(&hypr)?,
+    })
+}
+
+/// Read every window rule out of an already parsed config.
+pub(super) fn parsed_rules(hypr: &Hyprland) -> Result<Vec<ParsedRule>> {
+    let mut rules: Vec<ParsedRule> = vec![];
+    let keyword_rules = hypr
+        .all_windowrules()
+        .into_iter()
+        .filter_map(|rule| parse_keyword_rule(rule, false))
+        .chain(
+            hypr.all_windowrulesv2()
+                .into_iter()
+                .filter_map(|rule| parse_keyword_rule(rule, true)),
+        );
+    for rule in keyword_rules {
+        match rules
+            .iter_mut()
+            .find(|existing| existing.syntax == rule.syntax && existing.selector == rule.selector)
+        {
+            Some(existing) => existing.actions.extend(rule.actions),
+            None => rules.push(rule),
+        }
+    }
+
+    for name in hypr.windowrule_names() {
+        let values = hypr.config().get_special_category("windowrule", &name)?;
+        let mut selector = vec![];
+        let mut actions = vec![];
+        for (key, value) in values {
+            let value = value.to_string();
+            if value.is_empty() || key == "enable" || key == "name" {
+                continue;
+            }
+            match key.strip_prefix("match:") {
+                Some(field) => selector.push((v2_field_from_block(field).to_string(), value)),
+                None => {
+                    let keyword = v2_keyword_from_block(&key);
+                    let is_on = ["1", "on", "true", "yes"].contains(&value.as_str());
+                    match (TOGGLE_ACTIONS.contains(&keyword), is_on) {
+                        (true, true) => actions.push(keyword.to_string()),
+                        (true, false) => {}
+                        (false, _) => actions.push(format!("{keyword} {value}")),
+                    }
+                }
+            }
+        }
+        join_fullscreen_state(&mut selector);
+        // The special category hands its values back in hash order
+        selector.sort();
+        actions.sort();
+        rules.push(ParsedRule {
+            syntax: RuleSyntax::Block,
+            name: Some(name),
+            selector,
+            actions,
+        });
+    }
+
+    Ok(rules)
+}
+
+/// Fold the block syntax's `fullscreenstate_internal` and `fullscreenstate_client` into the
+/// single windowrulev2 `fullscreenstate` field, with `*` for whichever one is missing.
+fn join_fullscreen_state(selector: &mut Vec<(String, String)>) {
+    let mut take = |field: &str| {
+        let index = selector.iter().position(|(name, _)| name == field)?;
+        Some(selector.remove(index).1)
+    };
+    match (
+        take("fullscreenstate_internal"),
+        take("fullscreenstate_client"),
+    ) {
+        (None, None) => {}
+        (internal, client) => selector.push((
+            "fullscreenstate".to_string(),
+            format!(
+                "{} {}",
+                internal.as_deref().unwrap_or("*"),
+                client.as_deref().unwrap_or("*")
+            ),
+        )),
+    }
+}
+
+/// Split `float, class:^(kitty)$, title:^(foo)$` into its action and selector. Since 0.48 the
+/// `windowrule` keyword also takes v2 fields, so a v1 rule is one with a bare class or a lone
+/// `title:`.
+pub(super) fn parse_keyword_rule(rule: &str, is_v2: bool) -> Option<ParsedRule> {
+    let (action, selector) = rule.split_once(',')?;
+    let selector = selector.trim();
+    let fields = selector
+        .split(',')
+        .map(|field| field.trim())
+        .filter(|field| !field.is_empty())
+        .map(|field| match field.split_once(':') {
+            Some((name, pattern)) if V2_FIELDS.contains(&name) => {
+                Some((name.to_string(), pattern.to_string()))
+            }
+            _ => None,
+        })
+        .collect::<Option<Vec<(String, String)>>>()
+        .filter(|fields| !fields.is_empty());
+
+    let (syntax, selector) = match (fields, is_v2) {
+        (Some(fields), true) => (RuleSyntax::V2, fields),
+        (Some(fields), false) if fields.len() > 1 || fields[0].0 != "title" => {
+            (RuleSyntax::V2, fields)
+        }
+        (Some(fields), false) => (RuleSyntax::V1, fields),
+        (None, false) => (
+            RuleSyntax::V1,
+            vec![("class".to_string(), selector.to_string())],
+        ),
+        (None, true) => return None,
+    };
+    Some(ParsedRule {
+        syntax,
+        name: None,
+        selector,
+        actions: vec![action.trim().to_string()],
+    })
+}
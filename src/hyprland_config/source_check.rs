@@ -0,0 +1,285 @@
+use color_eyre::{Result, eyre, eyre::WrapErr};
+use hyprlang::Hyprland;
+use regex::Regex;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use super::journal;
+use crate::system_info::{get_data_dir, get_hyprland_dir, get_window_rules_dir};
+
+/// Hyprland gives up on `source` chains deeper than this too.
+const MAX_SOURCE_DEPTH: usize = 50;
+
+/// The files a source check reads and writes.
+#[derive(Debug, Clone)]
+pub struct SourcePaths {
+    /// `hyprland.conf`
+    pub main_config: PathBuf,
+    pub rules_dir: PathBuf,
+    /// Where the journal recording an inserted `source` line goes
+    pub data_dir: PathBuf,
+}
+
+impl SourcePaths {
+    /// The paths in use, from the environment and the config file.
+    pub fn get() -> Result<Self> {
+        Ok(Self {
+            main_config: get_hyprland_dir()?.join("hyprland.conf"),
+            rules_dir: get_window_rules_dir()?,
+            data_dir: get_data_dir()?,
+        })
+    }
+}
+
+/// Whether the user's hyprland config pulls in the managed rules directory.
+#[derive(Debug)]
+pub struct SourceCheck {
+    pub main_config: PathBuf,
+    pub rules_dir: PathBuf,
+    data_dir: PathBuf,
+    /// The config file and `source = ...` value that covers the rules directory, if any
+    pub sourced_by: Option<(PathBuf, String)>,
+    /// Every config file reached through `source` directives, the main config first
+    pub config_files: Vec<PathBuf>,
+}
+
+impl SourceCheck {
+    /// The value of a `source` line covering the rules directory, relative to the main config
+    /// when the directory lives next to it.
+    pub fn source_target(&self) -> String {
+        let config_dir = self.main_config.parent().unwrap_or(Path::new("/"));
+        let target = match self.rules_dir.strip_prefix(config_dir) {
+            Ok(relative) => relative.join("*"),
+            Err(_) => self.rules_dir.join("*"),
+        };
+        target.to_string_lossy().to_string()
+    }
+}
+
+/// Parse `hyprland.conf` and every file it sources, looking for a `source` directive whose
+/// path (globs included) covers a rule file in the managed rules directory.
+///
+/// hyprlang resolves `source` on its own but can't expand globs or `~`, so each file is parsed
+/// with its `source` lines blanked out and the directives are followed here, the way Hyprland
+/// does: relative to the file they appear in, after variable and `~` expansion.
+pub fn check_rules_sourced(paths: &SourcePaths) -> Result<SourceCheck> {
+    let main_config = paths.main_config.clone();
+    if !main_config.is_file() {
+        return Err(eyre::eyre!(
+            "No hyprland config at {}",
+            main_config.to_string_lossy()
+        ));
+    }
+
+    let mut check = SourceCheck {
+        main_config: main_config.clone(),
+        rules_dir: normalize(&paths.rules_dir),
+        data_dir: paths.data_dir.clone(),
+        sourced_by: None,
+        config_files: vec![],
+    };
+    visit_config(&main_config, &mut HashMap::new(), &mut check, 0)?;
+    Ok(check)
+}
+
+/// Append a `source` line for the rules directory to the main config and return it.
+///
+/// The line is checked by adding it through hyprlang's mutation API, but like
+/// [`super::edit_rule_file`] it is appended to the original text because hyprlang's serializer
+/// drops comments and blank lines.
+pub fn insert_rules_source(check: &SourceCheck) -> Result<String> {
+    let config_dir = check.main_config.parent().unwrap_or(Path::new("/"));
+    let target = check.source_target();
+    let line = format!("source = {target}");
+
+    let original = fs::read_to_string(&check.main_config)?;
+    let (without_sources, _) = split_sources(&original);
+    let mut hypr = Hyprland::new();
+    hypr.parse(&without_sources)?;
+    hypr.config_mut()
+        .add_handler_call("source", target.clone())?;
+    let is_added = hypr
+        .config()
+        .get_handler_calls("source")
+        .is_some_and(|calls| calls.contains(&target));
+    if !is_added
+        || !glob_matches(
+            &config_dir.join(&target),
+            &check.rules_dir.join("rule.conf"),
+        )
+    {
+        return Err(eyre::eyre!(
+            "\"{line}\" would not source the rules directory"
+        ));
+    }
+
+    let mut edited = original;
+    if !edited.is_empty() && !edited.ends_with('\n') {
+        edited.push('\n');
+    }
+    edited.push_str(&format!(
+        "\n# Rules written by hyprland-window-rule\n{line}\n"
+    ));
+    journal::write_file_in(&check.data_dir, &check.main_config, &edited, None)?;
+
+    Ok(line)
+}
+
+fn visit_config(
+    path: &Path,
+    variables: &mut HashMap<String, String>,
+    check: &mut SourceCheck,
+    depth: usize,
+) -> Result<()> {
+    let path = normalize(path);
+    if depth > MAX_SOURCE_DEPTH || check.config_files.contains(&path) {
+        return Ok(());
+    }
+    check.config_files.push(path.clone());
+
+    let text = fs::read_to_string(&path)
+        .wrap_err_with(|| format!("Failed to read {}", path.to_string_lossy()))?;
+    let (without_sources, sources) = split_sources(&text);
+    let mut hypr = Hyprland::new();
+    for (name, value) in variables.iter() {
+        hypr.config_mut().set_variable(name.clone(), value.clone());
+    }
+    hypr.parse(&without_sources)
+        .wrap_err_with(|| format!("Failed to parse {}", path.to_string_lossy()))?;
+    variables.extend(
+        hypr.variables()
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone())),
+    );
+
+    let base_dir = path.parent().unwrap_or(Path::new("/")).to_path_buf();
+    for source in sources {
+        let pattern = normalize(&base_dir.join(expand_source(&source, variables)));
+        if check.sourced_by.is_none() && glob_matches(&pattern, &check.rules_dir.join("rule.conf"))
+        {
+            check.sourced_by = Some((path.clone(), source.clone()));
+        }
+        for file in glob_paths(&pattern) {
+            visit_config(&file, variables, check, depth + 1)?;
+        }
+    }
+    Ok(())
+}
+
+/// Pull the `source = ...` values out of a config, turning their lines into empty comments so
+/// line numbers in hyprlang's errors still point at the right place. (A blank line would do
+/// too, except that hyprlang rejects a file made of nothing but blank lines.)
+fn split_sources(text: &str) -> (String, Vec<String>) {
+    let regex = Regex::new(r"^\s*source\s*=\s*(.*?)\s*$").unwrap();
+    let mut sources = vec![];
+    let mut without_sources = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let content = line.split('#').next().unwrap_or_default();
+        match regex.captures(content.trim_end_matches(['\n', '\r'])) {
+            Some(captures) => {
+                sources.push(captures[1].to_string());
+                without_sources.push_str("#\n");
+            }
+            None => without_sources.push_str(line),
+        }
+    }
+    (without_sources, sources)
+}
+
+/// Expand `$variables` and a leading `~` in a source path.
+fn expand_source(source: &str, variables: &HashMap<String, String>) -> String {
+    let regex = Regex::new(r"\$(\w+)").unwrap();
+    let expanded = regex.replace_all(source, |captures: &regex::Captures| {
+        variables
+            .get(&captures[1])
+            .cloned()
+            .unwrap_or_else(|| captures[0].to_string())
+    });
+    match (expanded.strip_prefix('~'), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{home}{rest}"),
+        _ => expanded.to_string(),
+    }
+}
+
+/// Resolve `.` and `..` without touching the file system, so paths to files that don't exist
+/// yet still compare equal.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn has_wildcard(component: &str) -> bool {
+    component.contains(['*', '?', '['])
+}
+
+/// Translate one path component of a glob into an anchored regex.
+fn component_regex(component: &str) -> Regex {
+    let mut pattern = String::from("^");
+    for character in component.chars() {
+        match character {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            '[' | ']' => pattern.push(character),
+            character => pattern.push_str(&regex::escape(&character.to_string())),
+        }
+    }
+    pattern.push('$');
+    Regex::new(&pattern).unwrap_or_else(|_| Regex::new(&regex::escape(component)).unwrap())
+}
+
+fn glob_matches(pattern: &Path, path: &Path) -> bool {
+    let pattern: Vec<Component> = pattern.components().collect();
+    let path: Vec<Component> = path.components().collect();
+    pattern.len() == path.len()
+        && pattern.iter().zip(&path).all(|(pattern, component)| {
+            let (pattern, component) = (
+                pattern.as_os_str().to_string_lossy(),
+                component.as_os_str().to_string_lossy(),
+            );
+            match has_wildcard(&pattern) {
+                true => {
+                    !component.starts_with('.') && component_regex(&pattern).is_match(&component)
+                }
+                false => pattern == component,
+            }
+        })
+}
+
+/// The files a glob points at, sorted like glob(3) returns them.
+fn glob_paths(pattern: &Path) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::new()];
+    for component in pattern.components() {
+        let text = component.as_os_str().to_string_lossy();
+        paths = match has_wildcard(&text) {
+            false => paths.into_iter().map(|path| path.join(component)).collect(),
+            true => {
+                let regex = component_regex(&text);
+                paths
+                    .iter()
+                    .filter_map(|path| fs::read_dir(path).ok())
+                    .flat_map(|entries| entries.filter_map(|entry| entry.ok()))
+                    .filter(|entry| {
+                        let name = entry.file_name().to_string_lossy().to_string();
+                        !name.starts_with('.') && regex.is_match(&name)
+                    })
+                    .map(|entry| entry.path())
+                    .collect()
+            }
+        };
+    }
+    paths.retain(|path| path.is_file());
+    paths.sort();
+    paths
+}
@@ -0,0 +1,264 @@
+use color_eyre::{Result, eyre};
+use hyprland::data::Version;
+use hyprland::prelude::*;
+use regex::Regex;
+use serde::Serialize;
+
+use super::SelectWindowBy;
+
+/// `windowrulev2` replaced the class-only `windowrule` syntax during the 0.6 betas.
+const WINDOWRULE_V2_SINCE: (u32, u32, u32) = (0, 6, 0);
+/// Named `windowrule { match:... }` blocks replaced both keyword forms in 0.53.
+const BLOCK_SINCE: (u32, u32, u32) = (0, 53, 0);
+
+/// The rule syntax a generated file is written in, see [`RuleSyntax::detect`].
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleSyntax {
+    /// `windowrule = float, ^(kitty)$`
+    V1,
+    /// `windowrulev2 = float, class:^(kitty)$, title:^(foo)$`
+    V2,
+    /// `windowrule[name] { match:class = ^(kitty)$ float = on }`
+    Block,
+}
+
+impl RuleSyntax {
+    /// Ask the running compositor for its version and pick the syntax it understands.
+    pub fn detect() -> Result<Self> {
+        let version = Version::get()?;
+        let version_string = version.version.unwrap_or(version.tag);
+        match parse_version(&version_string) {
+            Some(version) => Ok(Self::for_version(version)),
+            None => Err(eyre::eyre!(
+                "Unable to read a version number from hyprland's version \"{version_string}\""
+            )),
+        }
+    }
+
+    pub fn for_version(version: (u32, u32, u32)) -> Self {
+        if version >= BLOCK_SINCE {
+            RuleSyntax::Block
+        } else if version >= WINDOWRULE_V2_SINCE {
+            RuleSyntax::V2
+        } else {
+            RuleSyntax::V1
+        }
+    }
+
+    /// Render a window rule as config text. `name` is only used by the block syntax, which
+    /// needs every rule to have a unique identifier.
+    pub fn window_rule(
+        &self,
+        name: &str,
+        rules: &[String],
+        matchers: &[(SelectWindowBy, String)],
+    ) -> Result<String> {
+        match self {
+            RuleSyntax::V1 => {
+                let selector = match matchers {
+                    [(SelectWindowBy::Class, class)] => class.clone(),
+                    [(SelectWindowBy::Title, title)] => format!("title:{title}"),
+                    _ => {
+                        return Err(eyre::eyre!(
+                            "The v1 windowrule syntax can only match on a single class or title"
+                        ));
+                    }
+                };
+                Ok(rules
+                    .iter()
+                    .map(|rule| format!("windowrule = {rule}, {selector}\n"))
+                    .collect())
+            }
+            RuleSyntax::V2 => {
+                let selector = matchers
+                    .iter()
+                    .map(|(select_by, value)| format!("{}:{value}", v2_field(select_by)))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                Ok(rules
+                    .iter()
+                    .map(|rule| format!("windowrulev2 = {rule}, {selector}\n"))
+                    .collect())
+            }
+            RuleSyntax::Block => {
+                let on_workspace = [SelectWindowBy::Workspace, SelectWindowBy::OnWorkspace];
+                if on_workspace
+                    .iter()
+                    .all(|field| matchers.iter().any(|(select_by, _)| select_by == field))
+                {
+                    return Err(eyre::eyre!(
+                        "The block syntax matches workspaces through on_workspace alone, pick \
+                         either workspace or onworkspace"
+                    ));
+                }
+                let mut block = format!("windowrule[{name}] {{\n  name = {name}\n");
+                for (select_by, value) in matchers {
+                    match (select_by, value.split_once(' ')) {
+                        // The block syntax splits the pair into one field per mode
+                        (SelectWindowBy::FullscreenState, Some((internal, client))) => {
+                            block.push_str(&format!(
+                                "  match:fullscreenstate_internal = {internal}\n  \
+                                 match:fullscreenstate_client = {client}\n"
+                            ));
+                        }
+                        _ => block
+                            .push_str(&format!("  match:{} = {value}\n", block_field(select_by))),
+                    }
+                }
+                for rule in rules {
+                    let (keyword, value) = match rule.split_once(' ') {
+                        Some((keyword, value)) => (keyword, value),
+                        None => (rule.as_str(), "on"),
+                    };
+                    let (effect, value) = block_effect(keyword, value);
+                    block.push_str(&format!("  {effect} = {value}\n"));
+                }
+                block.push_str("}\n");
+                Ok(block)
+            }
+        }
+    }
+
+    /// Render a layer rule as config text. Layer rules never had a v2, so both keyword
+    /// syntaxes write `layerrule = ...` lines.
+    pub fn layer_rule(&self, name: &str, rules: &[String], namespace: &str) -> String {
+        match self {
+            RuleSyntax::V1 | RuleSyntax::V2 => rules
+                .iter()
+                .map(|rule| format!("layerrule = {rule}, {namespace}\n"))
+                .collect(),
+            RuleSyntax::Block => {
+                let mut block = format!(
+                    "layerrule[{name}] {{\n  name = {name}\n  match:namespace = {namespace}\n"
+                );
+                for rule in rules {
+                    let (keyword, value) = match rule.split_once(' ') {
+                        Some((keyword, value)) => (keyword, value),
+                        None => (rule.as_str(), "on"),
+                    };
+                    let (effect, value) = block_layer_effect(keyword, value);
+                    block.push_str(&format!("  {effect} = {value}\n"));
+                }
+                block.push_str("}\n");
+                block
+            }
+        }
+    }
+}
+
+fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let regex = Regex::new(r"(\d+)\.(\d+)\.(\d+)").unwrap();
+    let captures = regex.captures(version)?;
+    Some((
+        captures[1].parse().ok()?,
+        captures[2].parse().ok()?,
+        captures[3].parse().ok()?,
+    ))
+}
+
+pub(super) fn v2_field(select_by: &SelectWindowBy) -> &'static str {
+    match select_by {
+        SelectWindowBy::Title => "title",
+        SelectWindowBy::Class => "class",
+        SelectWindowBy::InitialClass => "initialClass",
+        SelectWindowBy::InitialTitle => "initialTitle",
+        SelectWindowBy::XWayland => "xwayland",
+        SelectWindowBy::Floating => "floating",
+        SelectWindowBy::Fullscreen => "fullscreen",
+        SelectWindowBy::Pinned => "pinned",
+        SelectWindowBy::Focus => "focus",
+        SelectWindowBy::Group => "group",
+        SelectWindowBy::Workspace => "workspace",
+        SelectWindowBy::OnWorkspace => "onworkspace",
+        SelectWindowBy::Tag => "tag",
+        SelectWindowBy::XdgTag => "xdgTag",
+        SelectWindowBy::Content => "content",
+        SelectWindowBy::FullscreenState => "fullscreenstate",
+    }
+}
+
+pub(super) fn select_by_from_v2_field(field: &str) -> Option<SelectWindowBy> {
+    SelectWindowBy::all()
+        .into_iter()
+        .find(|select_by| v2_field(select_by) == field)
+}
+
+/// `fullscreenstate` has no single block field, [`RuleSyntax::window_rule`] splits it up.
+fn block_field(select_by: &SelectWindowBy) -> &'static str {
+    match select_by {
+        SelectWindowBy::InitialClass => "initial_class",
+        SelectWindowBy::InitialTitle => "initial_title",
+        SelectWindowBy::Workspace | SelectWindowBy::OnWorkspace => "on_workspace",
+        SelectWindowBy::XdgTag => "xdg_tag",
+        select_by => v2_field(select_by),
+    }
+}
+
+/// Map a block `match:` field back onto its windowrulev2 name. The two fullscreen state fields
+/// are joined back up by the caller.
+pub(super) fn v2_field_from_block(field: &str) -> &str {
+    match field {
+        "initial_class" => "initialClass",
+        "initial_title" => "initialTitle",
+        "on_workspace" => "onworkspace",
+        "xdg_tag" => "xdgTag",
+        field => field,
+    }
+}
+
+/// Map a block effect back onto the windowrulev2 keyword [`super::WindowAction`] understands.
+pub(super) fn v2_keyword_from_block(effect: &str) -> &str {
+    match effect {
+        "min_size" => "minsize",
+        "max_size" => "maxsize",
+        "border_size" => "bordersize",
+        "no_blur" => "noblur",
+        "no_shadow" => "noshadow",
+        "no_anim" => "noanim",
+        "no_dim" => "nodim",
+        "no_focus" => "nofocus",
+        "no_initial_focus" => "noinitialfocus",
+        "dim_around" => "dimaround",
+        "keep_aspect_ratio" => "keepaspectratio",
+        "idle_inhibit" => "idleinhibit",
+        "suppress_event" => "suppressevent",
+        "stay_focused" => "stayfocused",
+        effect => effect,
+    }
+}
+
+/// The block syntax spells most effects in snake_case and dropped `noborder`.
+fn block_effect<'a>(keyword: &'a str, value: &'a str) -> (&'a str, &'a str) {
+    let effect = match keyword {
+        "noborder" => return ("border_size", "0"),
+        "minsize" => "min_size",
+        "maxsize" => "max_size",
+        "bordersize" => "border_size",
+        "noblur" => "no_blur",
+        "noshadow" => "no_shadow",
+        "noanim" => "no_anim",
+        "nodim" => "no_dim",
+        "nofocus" => "no_focus",
+        "noinitialfocus" => "no_initial_focus",
+        "dimaround" => "dim_around",
+        "keepaspectratio" => "keep_aspect_ratio",
+        "idleinhibit" => "idle_inhibit",
+        "suppressevent" => "suppress_event",
+        "stayfocused" => "stay_focused",
+        keyword => keyword,
+    };
+    (effect, value)
+}
+
+/// Layer effects went snake_case in the block syntax, and `ignorezero` became an alpha of 0.
+fn block_layer_effect<'a>(keyword: &'a str, value: &'a str) -> (&'a str, &'a str) {
+    let effect = match keyword {
+        "ignorezero" => return ("ignore_alpha", "0"),
+        "ignorealpha" => "ignore_alpha",
+        "noanim" => "no_anim",
+        "dimaround" => "dim_around",
+        keyword => keyword,
+    };
+    (effect, value)
+}
@@ -0,0 +1,86 @@
+use color_eyre::{Result, eyre};
+use hyprland::data::Workspace;
+use std::path::PathBuf;
+
+use super::{RuleOutput, hashed_rule_name, write_rule_file};
+
+/// The rules of a `workspace = ` line, each one written as `key:value`.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct WorkspaceRuleOptions {
+    pub monitor: Option<String>,
+    pub default: bool,
+    pub persistent: bool,
+    pub gaps_in: Option<String>,
+    pub gaps_out: Option<String>,
+    /// `orientation:left` and the like, written as `layoutopt:orientation:left`
+    pub layout_options: Vec<String>,
+    pub on_created_empty: Option<String>,
+}
+
+impl WorkspaceRuleOptions {
+    pub fn rules(&self) -> Vec<String> {
+        let values = [
+            self.monitor
+                .as_ref()
+                .map(|monitor| format!("monitor:{monitor}")),
+            self.default.then(|| "default:true".to_string()),
+            self.persistent.then(|| "persistent:true".to_string()),
+            self.gaps_in.as_ref().map(|gaps| format!("gapsin:{gaps}")),
+            self.gaps_out.as_ref().map(|gaps| format!("gapsout:{gaps}")),
+        ];
+        values
+            .into_iter()
+            .flatten()
+            .chain(
+                self.layout_options
+                    .iter()
+                    .map(|option| format!("layoutopt:{option}")),
+            )
+            .chain(
+                self.on_created_empty
+                    .iter()
+                    .map(|command| format!("on-created-empty:{command}")),
+            )
+            .collect()
+    }
+}
+
+/// How a workspace is named on the left of a workspace rule: its id when it has a plain
+/// numbered name, `special:...` as is, and `name:...` for everything else.
+pub fn workspace_selector(workspace: &Workspace) -> String {
+    if workspace.name == workspace.id.to_string() || workspace.name.starts_with("special:") {
+        workspace.name.clone()
+    } else {
+        format!("name:{}", workspace.name)
+    }
+}
+
+/// `workspace-{workspace}-{hash}`, so `name:foo` and `foo` don't end up in the same file.
+pub fn default_workspace_rule_name(workspace: &str) -> String {
+    hashed_rule_name(&format!("workspace-{workspace}"), workspace)
+}
+
+/// The `workspace = ` line for `workspace`, checked before anything is named or written.
+pub fn workspace_rule_text(workspace: &str, options: &WorkspaceRuleOptions) -> Result<String> {
+    let rules = options.rules();
+    if rules.is_empty() {
+        return Err(eyre::eyre!("A workspace rule needs at least one rule"));
+    }
+    // Rules are comma separated and a `#` would start a comment, neither can be escaped
+    if let Some(rule) = rules.iter().find(|rule| rule.contains([',', '#'])) {
+        return Err(eyre::eyre!(
+            "\"{rule}\" can't contain a comma or a # in a workspace rule"
+        ));
+    }
+
+    Ok(format!("workspace = {workspace}, {}\n", rules.join(", ")))
+}
+
+/// Write `rule_text`, from [`workspace_rule_text`], to `{rule_name}.conf`.
+pub fn create_workspace_rule_config(
+    rule_name: &str,
+    rule_text: &str,
+    output: RuleOutput,
+) -> Result<PathBuf> {
+    write_rule_file(rule_name, rule_text, output, None)
+}
@@ -1,13 +1,17 @@
+mod config;
 mod hyprland_config;
 mod shell_command;
 mod system_info;
+#[cfg(test)]
+mod test_client;
 mod tui;
 mod wayland;
 
-use std::time::Duration;
+use std::{process, time::Duration};
 
 use shell_command::commands::options_exec;
 use shell_command::types::*;
+use wayland::window_selector::Unpicked;
 
 use color_eyre::{Result, eyre};
 use eyre::Error;
@@ -19,29 +23,130 @@ use ratatui::{
 
 use clap::Parser;
 fn main() -> Result<()> {
+    let result = run();
+    // Scripts tell the ways a selection can end without a window apart by the exit code
+    if let Err(error) = &result
+        && let Some(unpicked) = error.downcast_ref::<Unpicked>()
+    {
+        eprintln!("{unpicked}");
+        process::exit(unpicked.exit_code());
+    }
+    result
+}
+
+fn run() -> Result<()> {
     //window_selector::create_window();
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
 
-    // You can check the value provided by positional arguments, or option arguments
+    // The positional name is shorthand for `generate --name`
+    if let Some(Commands::Generate { files, .. }) = &mut cli.command
+        && files.name.is_none()
+    {
+        files.name = cli.name.take();
+    }
     if let Some(name) = cli.name.as_deref() {
-        println!("Value for name: {name}");
+        return Err(eyre::eyre!(
+            "A rule name ({name}) can only be given to generate"
+        ));
     }
 
     if cli.version {
         options_exec::version();
     }
 
+    config::init()?;
+
     // You can check for the existence of subcommands, and if found use their
     // matches just as you would the top level cmd
     let cmd_result = match &cli.command {
         Some(Commands::Generate {
-            float,
-            tile,
-            fullscreen,
-            select_by,
+            actions,
+            select,
+            syntax,
+            apply,
+            dry_run,
+            stdout,
+            files,
+        }) => {
+            return shell_command::commands::generate::exec(
+                actions, select, syntax, apply, *dry_run, *stdout, files,
+            );
+        }
+        Some(Commands::Workspace {
+            workspace,
+            pick_monitor,
+            timeout,
+            rules,
+            naming,
+            dry_run,
+            stdout,
+        }) => {
+            return shell_command::commands::workspace::exec(
+                workspace.as_deref(),
+                *pick_monitor,
+                *timeout,
+                rules,
+                naming,
+                *dry_run,
+                *stdout,
+            );
+        }
+        Some(Commands::Layerrule {
+            namespace,
+            actions,
+            naming,
+            syntax,
+            dry_run,
+            stdout,
+        }) => {
+            return shell_command::commands::layerrule::exec(
+                namespace.as_deref(),
+                actions,
+                naming,
+                syntax,
+                *dry_run,
+                *stdout,
+            );
+        }
+        Some(Commands::List { json }) => {
+            return shell_command::commands::list::exec(*json);
+        }
+        Some(Commands::Edit { rule }) => {
+            return shell_command::commands::edit::exec(rule);
+        }
+        Some(Commands::Remove {
+            file,
+            class,
+            title,
+            pick,
+            timeout,
+            yes,
+            reload,
+            no_reload,
         }) => {
-            return shell_command::commands::generate::exec(&float, &tile, &fullscreen, &select_by);
+            return shell_command::commands::remove::exec(
+                file.as_deref(),
+                class.as_deref(),
+                title.as_deref(),
+                *pick,
+                *timeout,
+                *yes,
+                // Neither flag leaves it to the config file
+                (*reload || *no_reload).then_some(*reload),
+            );
+        }
+        Some(Commands::SourceCheck { yes }) => {
+            return shell_command::commands::source_check::exec(*yes);
+        }
+        Some(Commands::History { json }) => {
+            return shell_command::commands::history::exec(*json);
+        }
+        Some(Commands::Undo { count, yes }) => {
+            return shell_command::commands::undo::exec(*count, *yes);
         }
+        Some(Commands::Config { command }) => match command {
+            ConfigCommand::Show => return shell_command::commands::config::show(),
+        },
         None => Err(Error::msg("Unknown option")),
     };
 
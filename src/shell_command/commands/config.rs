@@ -0,0 +1,24 @@
+use color_eyre::Result;
+use std::fs;
+
+use crate::config::{self, config_path};
+use crate::system_info::get_window_rules_dir;
+
+/// Print the settings in effect as TOML, ready to be copied into the config file.
+pub fn show() -> Result<()> {
+    let path = config_path()?;
+    match fs::exists(&path)? {
+        true => println!("# {}", path.to_string_lossy()),
+        false => println!(
+            "# {} doesn't exist, these are the defaults",
+            path.to_string_lossy()
+        ),
+    }
+    // WINDOW_RULE_DIR can point somewhere else than rules_dir
+    println!(
+        "# Rule files are written to {}",
+        get_window_rules_dir()?.to_string_lossy()
+    );
+    print!("{}", toml::to_string_pretty(config::get())?);
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use color_eyre::{Result, eyre};
+use hyprland::data::Clients;
+use hyprland::prelude::*;
+
+use crate::hyprland_config::{client_extras, edit_rule_file, read_rule_files};
+use crate::tui::edit;
+
+pub fn exec(rule_name: &str) -> Result<()> {
+    let rule_files = read_rule_files()?;
+    let rule_file = rule_files
+        .iter()
+        .find(|rule_file| rule_file.has_name(rule_name))
+        .ok_or_else(|| {
+            eyre::eyre!("No rule file called {rule_name}, see `hyprland-window-rule list`")
+        })?;
+    let [rule] = rule_file.rules.as_slice() else {
+        return Err(eyre::eyre!(
+            "{} holds {} rules, only files with a single rule can be edited",
+            rule_file.file_name(),
+            rule_file.rules.len()
+        ));
+    };
+    let matchers = rule.matchers().ok_or_else(|| {
+        eyre::eyre!(
+            "{} matches on a field the editor doesn't know, edit it by hand",
+            rule_file.file_name()
+        )
+    })?;
+
+    // Editing works without hyprland running, a matching window only helps fill in patterns
+    let mut extras = client_extras().unwrap_or_default();
+    let client = Clients::get()
+        .ok()
+        .and_then(|clients| {
+            clients.into_iter().find(|client| {
+                let client_extras = extras.get(&client.address.to_string());
+                rule.matches_with(client, &client_extras.cloned().unwrap_or_default())
+            })
+        })
+        .map(|client| {
+            let client_extras = extras
+                .remove(&client.address.to_string())
+                .unwrap_or_default();
+            (client, client_extras)
+        });
+
+    let picked_client = client.as_ref().map(|(client, _)| client.clone());
+    match edit::app(&rule_file.file_name(), &rule.actions, &matchers, client)? {
+        Some(edited) => edit_rule_file(
+            rule_file,
+            rule,
+            &edited.window_options.rules(),
+            &edited.matchers,
+            picked_client.as_ref(),
+        ),
+        None => {
+            println!("Nothing was changed");
+            Ok(())
+        }
+    }
+}
@@ -1,48 +1,511 @@
+use std::path::PathBuf;
 use std::rc::Rc;
 
-use color_eyre::Result;
+use color_eyre::{Result, eyre};
+use hyprland::data::{Client, Monitors};
+use hyprland::prelude::*;
 
-use crate::hyprland_config::{WindowOptions, WindowPlacement, create_window_rule_config};
-use crate::shell_command::types::SelectWindowBy;
-use crate::tui::root;
+use crate::config;
+use crate::hyprland_config::{
+    self, ClientExtras, ClientQuery, ConflictKind, ConflictResolution, RuleConflict, RuleOutput,
+    SourcePaths, WindowAction, WindowOptions, WindowPlacement, apply_rule_file, client_matchers,
+    combined_matchers, create_window_rule_config, default_rule_name, find_client, find_conflicts,
+    geometry_actions, line_diff, parse_match,
+};
+use crate::shell_command::commands::source_check;
+use crate::shell_command::prompt::{choose, confirm};
+use crate::shell_command::rule_name::resolve_rule_name;
+use crate::shell_command::types::{
+    ApplyMode, GeometryUnits, IdleInhibitMode, OnConflict, RuleFileArgs, RuleSyntax, SelectArgs,
+    SelectWindowBy, SuppressEvent, WindowActionArgs,
+};
+use crate::tui::root::{self, ClickBindings, PromptDefaults, WindowSelection};
+
+/// The rule to write, along with the window it was made for when one was picked.
+struct NewRule {
+    /// For a rule covering several windows, the first one picked
+    client: Option<Client>,
+    /// Start of the default file name, the window's class
+    name_base: String,
+    window_options: WindowOptions,
+    matchers: Vec<(hyprland_config::SelectWindowBy, String)>,
+}
+
+/// What became of the existing rules the new one clashes with.
+enum ConflictOutcome {
+    /// The new rule went into an existing file
+    Resolved(PathBuf),
+    /// Nothing was folded in, the new rule still needs writing to its own file
+    WriteNew,
+    Cancelled,
+}
 
 pub fn exec(
-    float: &bool,
-    tile: &bool,
-    fullscreen: &bool,
-    select_by_list: &Vec<SelectWindowBy>,
+    actions: &WindowActionArgs,
+    select: &SelectArgs,
+    syntax: &RuleSyntax,
+    apply: &Option<ApplyMode>,
+    dry_run: bool,
+    stdout: bool,
+    files: &RuleFileArgs,
 ) -> Result<()> {
-    let window_options = WindowOptions {
-        fullscreen: fullscreen.clone(),
-        window_placement: match (float, tile) {
-            (true, false) => WindowPlacement::Float,
-            (false, true) => WindowPlacement::Tile,
-            _ => panic!("--float and --tile are mutually exclusive options"),
-        },
+    let syntax = match syntax {
+        RuleSyntax::Auto => hyprland_config::RuleSyntax::detect()?,
+        RuleSyntax::V1 => hyprland_config::RuleSyntax::V1,
+        RuleSyntax::V2 => hyprland_config::RuleSyntax::V2,
+        RuleSyntax::Block => hyprland_config::RuleSyntax::Block,
     };
 
-    let select_by_list: Vec<crate::hyprland_config::SelectWindowBy> = select_by_list
-        .iter()
-        .map(|select_by| match select_by {
-            SelectWindowBy::Title => crate::hyprland_config::SelectWindowBy::Title,
-            SelectWindowBy::Class => crate::hyprland_config::SelectWindowBy::Class,
-            SelectWindowBy::InitialClass => crate::hyprland_config::SelectWindowBy::InitialClass,
-            SelectWindowBy::InitialTitle => crate::hyprland_config::SelectWindowBy::InitialTitle,
+    let output = match (dry_run, stdout) {
+        (true, _) => RuleOutput::DryRun,
+        (_, true) => RuleOutput::Stdout,
+        _ => RuleOutput::File,
+    };
+
+    // Anything not given on the command line is asked for in the TUI after the window is picked
+    let window_options =
+        Some(window_options(actions)?).filter(|options| !options.rules().is_empty());
+
+    let new_rules = match (&select.match_tag, client_query(select)?) {
+        (Some(tag), _) => vec![tag_rule(tag, window_options)?],
+        (None, Some(query)) => {
+            let (client, extras) = find_client(&query)?;
+            let mut window_options = actions_without_prompt(
+                window_options,
+                "Picking the window on the command line skips the terminal UI",
+            )?;
+            if let Some(units) = &actions.remember_geometry {
+                window_options
+                    .actions
+                    .extend(remembered_geometry(&client, units)?);
+            }
+            let select_by_list: Vec<hyprland_config::SelectWindowBy> =
+                match select.select_by.is_empty() {
+                    true => config::get().select_by.iter().map(select_by).collect(),
+                    false => select.select_by.iter().map(select_by).collect(),
+                };
+            let matchers = client_matchers(&client, &extras, &select_by_list)?;
+            vec![NewRule {
+                name_base: client.class.clone(),
+                client: Some(client),
+                window_options,
+                matchers,
+            }]
+        }
+        (None, None) => {
+            if select.multi && !select.rule_per_window && actions.remember_geometry.is_some() {
+                return Err(eyre::eyre!(
+                    "The windows each have their own geometry, add --rule-per-window to remember it"
+                ));
+            }
+            let select_by_list: Vec<hyprland_config::SelectWindowBy> =
+                select.select_by.iter().map(select_by).collect();
+            let select_by_list = Some(Rc::new(select_by_list)).filter(|list| !list.is_empty());
+            let clicks = &config::get().clicks;
+            let defaults = PromptDefaults {
+                actions: config::get().actions.clone(),
+                select_by_list: config::get().select_by.iter().map(select_by).collect(),
+                clicks: ClickBindings {
+                    left: clicks.left.clone(),
+                    right: clicks.right.clone(),
+                    middle: clicks.middle.clone(),
+                    select_by_list: clicks.select_by.iter().map(select_by).collect(),
+                    shift_select_by_list: clicks.shift_select_by.iter().map(select_by).collect(),
+                },
+            };
+            let selection = root::app(
+                window_options,
+                select_by_list,
+                defaults,
+                select.multi,
+                select.timeout,
+            )?;
+            picked_rules(selection, actions, select.rule_per_window)?
+        }
+    };
+
+    let mut written = vec![];
+    for new_rule in &new_rules {
+        if let Some(file_path) = write_rule(new_rule, syntax, output, files)? {
+            written.push((file_path, new_rule.client.as_ref()));
+        }
+    }
+
+    // A rule Hyprland never loads does nothing, and a missing source line is the likeliest
+    // reason, so check before the rule is applied. The rule is written by then, so a question
+    // nobody can answer only fails the command once the rule is applied as well
+    let sourced = match output == RuleOutput::File && !written.is_empty() {
+        true => {
+            SourcePaths::get().and_then(|paths| source_check::ensure_sourced(&paths, files.yes))
+        }
+        false => Ok(()),
+    };
+
+    let apply_mode = match apply.as_ref().unwrap_or(&config::get().apply) {
+        ApplyMode::Off => None,
+        ApplyMode::Reload => Some(hyprland_config::ApplyMode::Reload),
+        ApplyMode::Keyword => Some(hyprland_config::ApplyMode::Keyword),
+    };
+    // The config's apply mode can't know whether anything was written
+    if output == RuleOutput::File
+        && let Some(apply_mode) = apply_mode
+    {
+        for (file_path, client) in written {
+            apply_rule_file(&file_path, apply_mode, client)?;
+        }
+    }
+
+    sourced
+}
+
+/// The rules for the windows picked in the TUI: one for the picked window, and for a
+/// multi-select one covering them all or one per window.
+fn picked_rules(
+    selection: WindowSelection,
+    actions: &WindowActionArgs,
+    rule_per_window: bool,
+) -> Result<Vec<NewRule>> {
+    if !rule_per_window {
+        let windows: Vec<(&Client, &ClientExtras)> = selection
+            .windows
+            .iter()
+            .map(|(client, extras)| (client, extras))
+            .collect();
+        let mut window_options = selection.window_options.clone();
+        if let (Some(units), [(client, _)]) = (&actions.remember_geometry, windows.as_slice()) {
+            window_options
+                .actions
+                .extend(remembered_geometry(client, units)?);
+        }
+        let matchers = combined_matchers(&windows, &selection.select_by_list)?;
+        let mut classes: Vec<&str> = vec![];
+        for (client, _) in &windows {
+            if !classes.contains(&client.class.as_str()) {
+                classes.push(&client.class);
+            }
+        }
+        return Ok(vec![NewRule {
+            name_base: classes.join("-"),
+            client: selection.windows.first().map(|(client, _)| client.clone()),
+            window_options,
+            matchers,
+        }]);
+    }
+
+    selection
+        .windows
+        .into_iter()
+        .map(|(client, extras)| {
+            let mut window_options = selection.window_options.clone();
+            if let Some(units) = &actions.remember_geometry {
+                window_options
+                    .actions
+                    .extend(remembered_geometry(&client, units)?);
+            }
+            let matchers = client_matchers(&client, &extras, &selection.select_by_list)?;
+            Ok(NewRule {
+                name_base: client.class.clone(),
+                client: Some(client),
+                window_options,
+                matchers,
+            })
         })
-        .collect();
-    let selected_client = root::app(Some(window_options), Some(Rc::new(select_by_list)))?;
-
-    match selected_client {
-        Some(selection) => {
-            create_window_rule_config(
-                selection.client.clone(),
-                &selection.window_options,
-                selection.select_by_list,
-            )
-            .expect("Failed generating config");
+        .collect()
+}
+
+/// Write one rule, folding it into an existing rule it clashes with if the user says so.
+/// Returns the file the rule ended up in, `None` if nothing was written.
+fn write_rule(
+    new_rule: &NewRule,
+    syntax: hyprland_config::RuleSyntax,
+    output: RuleOutput,
+    files: &RuleFileArgs,
+) -> Result<Option<PathBuf>> {
+    let client = new_rule.client.as_ref();
+    let matchers = &new_rule.matchers;
+    let rules = new_rule.window_options.rules();
+    let conflicts = match output {
+        RuleOutput::Stdout => vec![],
+        RuleOutput::File | RuleOutput::DryRun => find_conflicts(client, matchers, &rules)?,
+    };
+    let on_conflict = files
+        .on_conflict
+        .as_ref()
+        .map(|on_conflict| match on_conflict {
+            OnConflict::Merge => ConflictResolution::Merge,
+            OnConflict::Replace => ConflictResolution::Replace,
+            OnConflict::Keep => ConflictResolution::Keep,
+        });
+
+    match resolve_conflicts(&conflicts, &rules, matchers, on_conflict, output, client)? {
+        ConflictOutcome::Resolved(file_path) => Ok(Some(file_path)),
+        ConflictOutcome::WriteNew => {
+            let default_name = default_rule_name(&new_rule.name_base, matchers);
+            let Some(rule_name) = resolve_rule_name(
+                &default_name,
+                files.name.as_deref(),
+                &files.on_collision,
+                output,
+            )?
+            else {
+                println!("Nothing was written");
+                return Ok(None);
+            };
+            let file_path = create_window_rule_config(
+                &new_rule.window_options,
+                matchers,
+                &rule_name,
+                syntax,
+                output,
+                client,
+            )?;
+            Ok(Some(file_path))
         }
-        None => {}
+        ConflictOutcome::Cancelled => {
+            println!("Nothing was written");
+            Ok(None)
+        }
+    }
+}
+
+/// A rule matching every window tagged `tag`. There's no window to ask about, so the actions
+/// have to come from the command line or the config file's default actions.
+fn tag_rule(tag: &str, window_options: Option<WindowOptions>) -> Result<NewRule> {
+    if tag.is_empty() || tag.contains([' ', ',']) {
+        return Err(eyre::eyre!(
+            "\"{tag}\" isn't a tag name, they can't contain spaces or commas"
+        ));
+    }
+    let window_options = actions_without_prompt(
+        window_options,
+        "--match-tag writes the rule without picking a window",
+    )?;
+    Ok(NewRule {
+        client: None,
+        name_base: format!("tag-{tag}"),
+        window_options,
+        matchers: vec![(hyprland_config::SelectWindowBy::Tag, tag.to_string())],
+    })
+}
+
+/// The actions for a rule written without the terminal UI: the command line's, or else the
+/// config file's default actions. `reason` explains why they can't be asked for.
+fn actions_without_prompt(
+    window_options: Option<WindowOptions>,
+    reason: &str,
+) -> Result<WindowOptions> {
+    match window_options {
+        Some(window_options) => Ok(window_options),
+        None if !config::get().actions.is_empty() => {
+            WindowOptions::from_rules(&config::get().actions)
+        }
+        None => Err(eyre::eyre!(
+            "{reason}, give the rule's actions too or set default actions in the config file"
+        )),
+    }
+}
+
+/// The window picked by `--address`, `--pid`, `--active` or `--match`, if one of them was given.
+fn client_query(select: &SelectArgs) -> Result<Option<ClientQuery>> {
+    if let Some(address) = &select.address {
+        return Ok(Some(ClientQuery::Address(address.clone())));
+    }
+    if let Some(pid) = select.pid {
+        return Ok(Some(ClientQuery::Pid(pid)));
+    }
+    if select.active {
+        return Ok(Some(ClientQuery::Active));
+    }
+    if select.match_window.is_empty() {
+        return Ok(None);
+    }
+    let matchers = select
+        .match_window
+        .iter()
+        .map(|selector| parse_match(selector))
+        .collect::<Result<Vec<(hyprland_config::SelectWindowBy, String)>>>()?;
+    Ok(Some(ClientQuery::Matching(matchers)))
+}
+
+fn remembered_geometry(client: &Client, units: &GeometryUnits) -> Result<Vec<WindowAction>> {
+    let units = match units {
+        GeometryUnits::Pixels => hyprland_config::GeometryUnits::Pixels,
+        GeometryUnits::Percent => hyprland_config::GeometryUnits::Percent,
+    };
+    geometry_actions(client, &Monitors::get()?, units)
+}
+
+/// Walk through the clashing rules, asking how to resolve each one unless `on_conflict` says.
+/// A dry run only lists them.
+fn resolve_conflicts(
+    conflicts: &[RuleConflict],
+    rules: &[String],
+    matchers: &[(hyprland_config::SelectWindowBy, String)],
+    on_conflict: Option<ConflictResolution>,
+    output: RuleOutput,
+    client: Option<&Client>,
+) -> Result<ConflictOutcome> {
+    for conflict in conflicts {
+        println!(
+            "{} already has a rule for this window:",
+            conflict.rule_file.file_name()
+        );
+        println!(
+            "    {} -> {}",
+            conflict.rule.selector_text(),
+            conflict.rule.actions.join(", ")
+        );
+        if let ConflictKind::Contradicts(contradictions) = &conflict.kind {
+            for (existing, new) in contradictions {
+                println!("  it sets \"{existing}\" where the new rule sets \"{new}\"");
+            }
+        }
+        if output == RuleOutput::DryRun {
+            continue;
+        }
+
+        let resolution = match on_conflict {
+            Some(resolution) => resolution,
+            None => match choose(
+                "Merge into it, replace it, or keep both?",
+                &["merge", "replace", "keep"],
+                "--on-conflict",
+            )? {
+                Some("merge") => ConflictResolution::Merge,
+                Some("replace") => ConflictResolution::Replace,
+                Some(_) => ConflictResolution::Keep,
+                None => return Ok(ConflictOutcome::Cancelled),
+            },
+        };
+        let Some(contents) = conflict.preview(resolution, rules, matchers)? else {
+            continue;
+        };
+
+        let path = conflict.rule_file.path.to_string_lossy();
+        println!("--- {path}\n+++ {path}");
+        let original = std::fs::read_to_string(&conflict.rule_file.path)?;
+        for line in line_diff(&original, &contents) {
+            println!("{line}");
+        }
+        if on_conflict.is_none() && !confirm("Write this change?", "--on-conflict")? {
+            return Ok(ConflictOutcome::Cancelled);
+        }
+
+        conflict.resolve(resolution, rules, matchers, client)?;
+        return Ok(ConflictOutcome::Resolved(conflict.rule_file.path.clone()));
+    }
+    Ok(ConflictOutcome::WriteNew)
+}
+
+fn select_by(select_by: &SelectWindowBy) -> hyprland_config::SelectWindowBy {
+    match select_by {
+        SelectWindowBy::Title => hyprland_config::SelectWindowBy::Title,
+        SelectWindowBy::Class => hyprland_config::SelectWindowBy::Class,
+        SelectWindowBy::InitialClass => hyprland_config::SelectWindowBy::InitialClass,
+        SelectWindowBy::InitialTitle => hyprland_config::SelectWindowBy::InitialTitle,
+        SelectWindowBy::XWayland => hyprland_config::SelectWindowBy::XWayland,
+        SelectWindowBy::Floating => hyprland_config::SelectWindowBy::Floating,
+        SelectWindowBy::Fullscreen => hyprland_config::SelectWindowBy::Fullscreen,
+        SelectWindowBy::Pinned => hyprland_config::SelectWindowBy::Pinned,
+        SelectWindowBy::Focus => hyprland_config::SelectWindowBy::Focus,
+        SelectWindowBy::Group => hyprland_config::SelectWindowBy::Group,
+        SelectWindowBy::Workspace => hyprland_config::SelectWindowBy::Workspace,
+        SelectWindowBy::OnWorkspace => hyprland_config::SelectWindowBy::OnWorkspace,
+        SelectWindowBy::Tag => hyprland_config::SelectWindowBy::Tag,
+        SelectWindowBy::XdgTag => hyprland_config::SelectWindowBy::XdgTag,
+        SelectWindowBy::Content => hyprland_config::SelectWindowBy::Content,
+        SelectWindowBy::FullscreenState => hyprland_config::SelectWindowBy::FullscreenState,
+    }
+}
+
+fn window_options(actions: &WindowActionArgs) -> Result<WindowOptions> {
+    let window_placement = match (actions.float, actions.tile) {
+        (true, _) => Some(WindowPlacement::Float),
+        (_, true) => Some(WindowPlacement::Tile),
+        // Hyprland only sizes and moves floating windows
+        _ if actions.remember_geometry.is_some() => Some(WindowPlacement::Float),
+        _ => None,
+    };
+
+    let flags = [
+        (actions.fullscreen, WindowAction::Fullscreen),
+        (actions.maximize, WindowAction::Maximize),
+        (actions.pseudo, WindowAction::Pseudo),
+        (actions.center, WindowAction::Center),
+        (actions.pin, WindowAction::Pin),
+        (actions.no_blur, WindowAction::NoBlur),
+        (actions.no_shadow, WindowAction::NoShadow),
+        (actions.no_border, WindowAction::NoBorder),
+        (actions.no_anim, WindowAction::NoAnim),
+        (actions.no_dim, WindowAction::NoDim),
+        (actions.no_focus, WindowAction::NoFocus),
+        (actions.no_initial_focus, WindowAction::NoInitialFocus),
+        (actions.dim_around, WindowAction::DimAround),
+        (actions.keep_aspect_ratio, WindowAction::KeepAspectRatio),
+        (actions.stay_focused, WindowAction::StayFocused),
+    ];
+
+    let values = [
+        actions.size.clone().map(WindowAction::Size),
+        actions.min_size.clone().map(WindowAction::MinSize),
+        actions.max_size.clone().map(WindowAction::MaxSize),
+        actions.move_to.clone().map(WindowAction::Move),
+        actions
+            .workspace
+            .clone()
+            .map(|workspace| WindowAction::Workspace {
+                workspace,
+                silent: actions.silent,
+            }),
+        actions.monitor.clone().map(WindowAction::Monitor),
+        actions.opacity.clone().map(WindowAction::Opacity),
+        actions.rounding.map(WindowAction::Rounding),
+        actions.border_size.map(WindowAction::BorderSize),
+        actions.idle_inhibit.as_ref().map(|mode| {
+            WindowAction::IdleInhibit(match mode {
+                IdleInhibitMode::None => hyprland_config::IdleInhibitMode::None,
+                IdleInhibitMode::Always => hyprland_config::IdleInhibitMode::Always,
+                IdleInhibitMode::Focus => hyprland_config::IdleInhibitMode::Focus,
+                IdleInhibitMode::Fullscreen => hyprland_config::IdleInhibitMode::Fullscreen,
+            })
+        }),
+        (!actions.suppress_event.is_empty()).then(|| {
+            WindowAction::SuppressEvent(
+                actions
+                    .suppress_event
+                    .iter()
+                    .map(|event| match event {
+                        SuppressEvent::Fullscreen => hyprland_config::SuppressEvent::Fullscreen,
+                        SuppressEvent::Maximize => hyprland_config::SuppressEvent::Maximize,
+                        SuppressEvent::Activate => hyprland_config::SuppressEvent::Activate,
+                        SuppressEvent::ActivateFocus => {
+                            hyprland_config::SuppressEvent::ActivateFocus
+                        }
+                        SuppressEvent::FullscreenOutput => {
+                            hyprland_config::SuppressEvent::FullscreenOutput
+                        }
+                    })
+                    .collect(),
+            )
+        }),
+    ];
+
+    let tag = match &actions.tag {
+        Some(tag) => Some(
+            WindowAction::from_rule(&format!("tag {tag}")).ok_or_else(|| {
+                eyre::eyre!("\"{tag}\" isn't a tag name, they can't contain spaces or commas")
+            })?,
+        ),
+        None => None,
     };
 
-    Ok(())
+    Ok(WindowOptions {
+        window_placement,
+        actions: flags
+            .into_iter()
+            .filter_map(|(enabled, action)| enabled.then_some(action))
+            .chain(values.into_iter().flatten())
+            .chain(tag)
+            .collect(),
+    })
 }
@@ -0,0 +1,58 @@
+use color_eyre::Result;
+
+use super::list::print_table;
+use crate::hyprland_config::{ChangeKind, JournalEntry, read_journal};
+
+pub fn exec(json: bool) -> Result<()> {
+    let mut entries = read_journal()?;
+    entries.reverse();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No changes have been recorded yet");
+        return Ok(());
+    }
+
+    // Numbered newest first, so `undo 3` undoes the first three rows
+    let rows: Vec<[String; 5]> = entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            [
+                (index + 1).to_string(),
+                entry.time_text(),
+                change_text(entry.kind).to_string(),
+                entry.path.to_string_lossy().to_string(),
+                match &entry.client {
+                    Some(client) => format!("{}: {}", client.class, client.title),
+                    None => "-".to_string(),
+                },
+            ]
+        })
+        .collect();
+    print_table(["#", "TIME", "CHANGE", "FILE", "WINDOW"], &rows);
+
+    Ok(())
+}
+
+pub(super) fn change_text(kind: ChangeKind) -> &'static str {
+    match kind {
+        ChangeKind::Create => "create",
+        ChangeKind::Overwrite => "overwrite",
+        ChangeKind::Delete => "delete",
+    }
+}
+
+/// `2026-10-18T09:30:00Z create /path/to/file` for the undo listing.
+pub(super) fn entry_summary(entry: &JournalEntry) -> String {
+    format!(
+        "{} {} {}",
+        entry.time_text(),
+        change_text(entry.kind),
+        entry.path.to_string_lossy()
+    )
+}
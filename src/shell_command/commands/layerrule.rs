@@ -0,0 +1,95 @@
+use color_eyre::{Result, eyre};
+use hyprland::data::Layers;
+use hyprland::prelude::*;
+
+use crate::hyprland_config::{
+    self, LayerAction, RuleOutput, create_layer_rule_config, default_layer_rule_name,
+    layer_surfaces,
+};
+use crate::shell_command::rule_name::resolve_rule_name;
+use crate::shell_command::types::{LayerActionArgs, RuleNameArgs, RuleSyntax};
+use crate::tui;
+
+pub fn exec(
+    namespace: Option<&str>,
+    actions: &LayerActionArgs,
+    naming: &RuleNameArgs,
+    syntax: &RuleSyntax,
+    dry_run: bool,
+    stdout: bool,
+) -> Result<()> {
+    let syntax = match syntax {
+        RuleSyntax::Auto => hyprland_config::RuleSyntax::detect()?,
+        RuleSyntax::V1 => hyprland_config::RuleSyntax::V1,
+        RuleSyntax::V2 => hyprland_config::RuleSyntax::V2,
+        RuleSyntax::Block => hyprland_config::RuleSyntax::Block,
+    };
+
+    let output = match (dry_run, stdout) {
+        (true, _) => RuleOutput::DryRun,
+        (_, true) => RuleOutput::Stdout,
+        _ => RuleOutput::File,
+    };
+
+    let layer_actions = layer_actions(actions);
+    let (namespace, layer_actions) = match namespace {
+        Some(_) if layer_actions.is_empty() => {
+            return Err(eyre::eyre!(
+                "Pass at least one action along with the namespace, or leave the namespace out to pick both in the terminal"
+            ));
+        }
+        Some(namespace) => (namespace.to_string(), layer_actions),
+        None => {
+            let layers = layer_surfaces(Layers::get()?);
+            match tui::layer::app(layers, layer_actions.is_empty())? {
+                Some(layer_rule) if layer_actions.is_empty() => {
+                    (layer_rule.namespace, layer_rule.actions)
+                }
+                Some(layer_rule) => (layer_rule.namespace, layer_actions),
+                None => return Ok(()),
+            }
+        }
+    };
+
+    let Some(rule_name) = resolve_rule_name(
+        &default_layer_rule_name(&namespace),
+        naming.name.as_deref(),
+        &naming.on_collision,
+        output,
+    )?
+    else {
+        println!("Nothing was written");
+        return Ok(());
+    };
+    create_layer_rule_config(&rule_name, &namespace, &layer_actions, syntax, output)?;
+    Ok(())
+}
+
+fn layer_actions(args: &LayerActionArgs) -> Vec<LayerAction> {
+    let mut actions = Vec::new();
+    if args.blur {
+        actions.push(LayerAction::Blur);
+    }
+    if args.ignore_zero {
+        actions.push(LayerAction::IgnoreZero);
+    }
+    if let Some(alpha) = args.ignore_alpha {
+        actions.push(LayerAction::IgnoreAlpha(alpha));
+    }
+    if args.no_anim {
+        actions.push(LayerAction::NoAnim);
+    }
+    if let Some(style) = &args.animation {
+        actions.push(LayerAction::Animation(style.clone()));
+    }
+    if args.dim_around {
+        actions.push(LayerAction::DimAround);
+    }
+    if let Some(xray) = args.xray {
+        actions.push(LayerAction::Xray(xray == 1));
+    }
+    if let Some(order) = args.order {
+        actions.push(LayerAction::Order(order));
+    }
+    actions
+}
@@ -0,0 +1,104 @@
+use color_eyre::Result;
+use hyprland::data::{Client, Clients};
+use hyprland::prelude::*;
+use serde::Serialize;
+
+use std::collections::HashMap;
+
+use crate::hyprland_config::{ClientExtras, ParsedRule, RuleFile, client_extras, read_rule_files};
+
+#[derive(Serialize)]
+struct ListedRule<'a> {
+    file: String,
+    #[serde(flatten)]
+    rule: &'a ParsedRule,
+    /// Addresses of the open clients the rule applies to
+    matches: Vec<String>,
+}
+
+pub fn exec(json: bool) -> Result<()> {
+    let rule_files = read_rule_files()?;
+    let clients = Clients::get()?.to_vec();
+    let extras = client_extras()?;
+    let listed_rules = listed_rules(&rule_files, &clients, &extras);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&listed_rules)?);
+        return Ok(());
+    }
+
+    if listed_rules.is_empty() {
+        println!("No window rules have been generated yet");
+        return Ok(());
+    }
+
+    let rows: Vec<[String; 4]> = listed_rules
+        .iter()
+        .map(|listed| {
+            [
+                listed.file.clone(),
+                listed.rule.selector_text(),
+                listed.rule.actions.join(", "),
+                match listed.matches.len() {
+                    0 => "no".to_string(),
+                    count => format!("yes ({count})"),
+                },
+            ]
+        })
+        .collect();
+    print_table(["FILE", "SELECTOR", "ACTIONS", "MATCHES"], &rows);
+
+    Ok(())
+}
+
+/// `extras` from [`client_extras`], so tag, xdgTag and content selectors can match too.
+fn listed_rules<'a>(
+    rule_files: &'a [RuleFile],
+    clients: &[Client],
+    extras: &HashMap<String, ClientExtras>,
+) -> Vec<ListedRule<'a>> {
+    rule_files
+        .iter()
+        .flat_map(|rule_file| {
+            rule_file.rules.iter().map(|rule| ListedRule {
+                file: rule_file.file_name(),
+                rule,
+                matches: clients
+                    .iter()
+                    .filter(|client| {
+                        let client_extras = extras
+                            .get(&client.address.to_string())
+                            .cloned()
+                            .unwrap_or_default();
+                        rule.matches_with(client, &client_extras)
+                    })
+                    .map(|client| client.address.to_string())
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+pub(super) fn print_table<const N: usize>(header: [&str; N], rows: &[[String; N]]) {
+    let mut widths = header.map(|column| column.chars().count());
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: [&str; N]| {
+        cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<String>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    println!("{}", format_row(header));
+    for row in rows {
+        println!("{}", format_row(row.each_ref().map(|cell| cell.as_str())));
+    }
+}
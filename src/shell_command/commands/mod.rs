@@ -1,2 +1,11 @@
+pub mod config;
+pub mod edit;
 pub mod generate;
+pub mod history;
+pub mod layerrule;
+pub mod list;
 pub mod options_exec;
+pub mod remove;
+pub mod source_check;
+pub mod undo;
+pub mod workspace;
@@ -2,7 +2,7 @@ use crate::system_info::*;
 use clap::Parser;
 
 #[derive(Parser, Debug)]
-#[command(version = version(), about = "ratatui template with crossterm and tokio")]
+#[command(version = version(), about = "Generate Hyprland window rules by clicking on windows")]
 struct Args {
     /// App tick rate
     #[arg(short, long, default_value_t = 1000)]
@@ -12,7 +12,7 @@ struct Args {
 pub fn version() -> String {
     let author = clap::crate_authors!();
 
-    let commit_hash = env!("RATATUI_TEMPLATE_GIT_INFO");
+    let commit_hash = env!("HYPRLAND_WINDOW_RULE_GIT_INFO");
 
     // let current_exe_path = PathBuf::from(clap::crate_name!()).display().to_string();
     let config_dir_path = get_config_dir().unwrap().display().to_string();
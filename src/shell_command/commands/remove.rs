@@ -0,0 +1,92 @@
+use color_eyre::{Result, eyre};
+use hyprland::data::{Clients, Monitors};
+use hyprland::prelude::*;
+use std::time::Duration;
+
+use crate::config;
+use crate::hyprland_config::{RuleFile, extras_for, read_rule_files, remove_file};
+use crate::shell_command::prompt::confirm;
+use crate::wayland::window_selector::pick_client;
+
+pub fn exec(
+    file: Option<&str>,
+    class: Option<&str>,
+    title: Option<&str>,
+    pick: bool,
+    timeout: Option<Duration>,
+    yes: bool,
+    reload: Option<bool>,
+) -> Result<()> {
+    let rule_files = read_rule_files()?;
+
+    let mut picked_client = None;
+    let to_remove: Vec<&RuleFile> = if pick {
+        let client = pick_client(Clients::get()?, Monitors::get()?, timeout)?;
+        let extras = extras_for(&client)?;
+        let to_remove = rule_files
+            .iter()
+            .filter(|rule_file| {
+                rule_file
+                    .rules
+                    .iter()
+                    .any(|rule| rule.matches_with(&client, &extras))
+            })
+            .collect();
+        picked_client = Some(client);
+        to_remove
+    } else {
+        let filters: Vec<(&str, &str)> = [("class", class), ("title", title)]
+            .into_iter()
+            .filter_map(|(field, value)| value.map(|value| (field, value)))
+            .collect();
+        if file.is_none() && filters.is_empty() {
+            return Err(eyre::eyre!(
+                "Pass a rule file name, --class, --title or --pick to choose what to remove"
+            ));
+        }
+        rule_files
+            .iter()
+            .filter(|rule_file| file.is_none_or(|file| rule_file.has_name(file)))
+            .filter(|rule_file| {
+                filters.is_empty()
+                    || rule_file
+                        .rules
+                        .iter()
+                        .any(|rule| rule.matches_values(&filters))
+            })
+            .collect()
+    };
+
+    if to_remove.is_empty() {
+        println!("No rule files matched");
+        return Ok(());
+    }
+
+    println!("These rule files will be removed:");
+    for rule_file in &to_remove {
+        println!("  {}", rule_file.path.to_string_lossy());
+        for rule in &rule_file.rules {
+            println!(
+                "    {} -> {}",
+                rule.selector_text(),
+                rule.actions.join(", ")
+            );
+        }
+    }
+    if !yes && !confirm(&format!("Remove {} file(s)?", to_remove.len()), "--yes")? {
+        println!("Nothing was removed");
+        return Ok(());
+    }
+
+    for rule_file in &to_remove {
+        remove_file(&rule_file.path, picked_client.as_ref())?;
+    }
+    println!("Removed {} file(s)", to_remove.len());
+
+    if reload.unwrap_or(config::get().reload_after_remove) {
+        hyprland::ctl::reload::call()?;
+        println!("Reloaded hyprland");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use color_eyre::Result;
+
+use crate::hyprland_config::{SourceCheck, SourcePaths, check_rules_sourced, insert_rules_source};
+use crate::shell_command::prompt::confirm;
+
+pub fn exec(yes: bool) -> Result<()> {
+    let check = check_rules_sourced(&SourcePaths::get()?)?;
+    match &check.sourced_by {
+        Some((file, source)) => {
+            println!(
+                "{} is loaded by \"source = {source}\" in {}",
+                check.rules_dir.to_string_lossy(),
+                file.to_string_lossy()
+            );
+            Ok(())
+        }
+        None => offer_insert(&check, yes),
+    }
+}
+
+/// Run once `generate` has written a rule: quiet when the rules directory is sourced, otherwise
+/// point out that the rule won't be loaded and offer to fix it, or fix it straight away with
+/// `yes`. A config that can't be checked is only reported.
+pub fn ensure_sourced(paths: &SourcePaths, yes: bool) -> Result<()> {
+    let check = match check_rules_sourced(paths) {
+        Ok(check) => check,
+        Err(error) => {
+            println!("Unable to check that the rules directory is sourced: {error}");
+            return Ok(());
+        }
+    };
+    match check.sourced_by {
+        Some(_) => Ok(()),
+        None => offer_insert(&check, yes),
+    }
+}
+
+fn offer_insert(check: &SourceCheck, yes: bool) -> Result<()> {
+    println!(
+        "{} is not sourced by {} or the {} file(s) it sources, so its rules are never loaded",
+        check.rules_dir.to_string_lossy(),
+        check.main_config.to_string_lossy(),
+        check.config_files.len() - 1
+    );
+    let question = format!(
+        "Add \"source = {}\" to {}?",
+        check.source_target(),
+        check.main_config.to_string_lossy()
+    );
+    if !yes && !confirm(&question, "--yes")? {
+        println!("The config was left as it is");
+        return Ok(());
+    }
+
+    let line = insert_rules_source(check)?;
+    println!(
+        "Added \"{line}\" to {}",
+        check.main_config.to_string_lossy()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::ensure_sourced;
+    use crate::hyprland_config::SourcePaths;
+
+    #[test]
+    fn ensure_sourced_inserts_the_source_line_once() {
+        let configs = [
+            "$mod = SUPER\nbind = $mod, Q, exec, kitty # terminal\n",
+            "$mod = SUPER\nsource = colors.conf\nbind = $mod, Q, exec, kitty",
+        ];
+        for original in configs {
+            let config_dir = tempfile::tempdir().unwrap();
+            let paths = SourcePaths {
+                main_config: config_dir.path().join("hyprland.conf"),
+                rules_dir: config_dir.path().join("window-rules"),
+                data_dir: config_dir.path().join("data"),
+            };
+            fs::create_dir(&paths.rules_dir).unwrap();
+            fs::write(config_dir.path().join("colors.conf"), "$accent = red\n").unwrap();
+            fs::write(&paths.main_config, original).unwrap();
+
+            ensure_sourced(&paths, true).unwrap();
+            let edited = fs::read_to_string(&paths.main_config).unwrap();
+            assert!(edited.starts_with(original), "{edited:?}");
+            assert_eq!(edited.matches("source = window-rules/*").count(), 1);
+            let journal = fs::read_to_string(paths.data_dir.join("journal.jsonl")).unwrap();
+            assert_eq!(journal.lines().count(), 1);
+
+            // Sourced now, so there's nothing left to ask about
+            ensure_sourced(&paths, false).unwrap();
+            assert_eq!(fs::read_to_string(&paths.main_config).unwrap(), edited);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use color_eyre::{Result, eyre};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::history::entry_summary;
+use crate::hyprland_config::{ChangeKind, read_journal, truncate_journal};
+use crate::shell_command::prompt::confirm;
+
+/// Undo the latest `count` changes, newest first, and drop them from the journal.
+pub fn exec(count: usize, yes: bool) -> Result<()> {
+    let entries = read_journal()?;
+    if entries.is_empty() {
+        println!("Nothing to undo");
+        return Ok(());
+    }
+    if count == 0 || count > entries.len() {
+        return Err(eyre::eyre!(
+            "There are {} recorded change(s), pick between 1 and {0}",
+            entries.len()
+        ));
+    }
+
+    let keep = entries.len() - count;
+    let to_undo = &entries[keep..];
+    println!("These changes will be undone:");
+    // Undoing a newer change to the same file puts it back first, so compare against that
+    let mut undone: HashMap<&Path, Option<&str>> = HashMap::new();
+    for entry in to_undo.iter().rev() {
+        println!("  {}", entry_summary(entry));
+        let current = match undone.get(entry.path.as_path()) {
+            Some(contents) => contents.map(String::from),
+            None => entry.current_contents()?,
+        };
+        if current != entry.contents {
+            println!("    the file has changed since, undoing throws that change away");
+        }
+        undone.insert(&entry.path, entry.previous.as_deref());
+    }
+    if !yes && !confirm(&format!("Undo {count} change(s)?"), "--yes")? {
+        println!("Nothing was undone");
+        return Ok(());
+    }
+
+    for entry in to_undo.iter().rev() {
+        entry.restore()?;
+        let path = entry.path.to_string_lossy();
+        match entry.kind {
+            ChangeKind::Create => println!("Removed {path}"),
+            ChangeKind::Overwrite | ChangeKind::Delete => println!("Restored {path}"),
+        }
+    }
+    truncate_journal(keep)?;
+
+    Ok(())
+}
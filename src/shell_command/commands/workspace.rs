@@ -0,0 +1,71 @@
+use color_eyre::Result;
+use hyprland::data::{Clients, Monitors, Workspaces};
+use hyprland::prelude::*;
+use std::time::Duration;
+
+use crate::hyprland_config::{
+    RuleOutput, WorkspaceRuleOptions, create_workspace_rule_config, default_workspace_rule_name,
+    workspace_rule_text, workspace_selector,
+};
+use crate::shell_command::rule_name::resolve_rule_name;
+use crate::shell_command::types::{RuleNameArgs, WorkspaceRuleArgs};
+use crate::tui;
+use crate::wayland::window_selector::pick_monitor;
+
+pub fn exec(
+    workspace: Option<&str>,
+    pick: bool,
+    timeout: Option<Duration>,
+    rules: &WorkspaceRuleArgs,
+    naming: &RuleNameArgs,
+    dry_run: bool,
+    stdout: bool,
+) -> Result<()> {
+    let output = match (dry_run, stdout) {
+        (true, _) => RuleOutput::DryRun,
+        (_, true) => RuleOutput::Stdout,
+        _ => RuleOutput::File,
+    };
+    let mut options = WorkspaceRuleOptions {
+        monitor: rules.monitor.clone(),
+        default: rules.default,
+        persistent: rules.persistent,
+        gaps_in: rules.gaps_in.clone(),
+        gaps_out: rules.gaps_out.clone(),
+        layout_options: rules.layout_options.clone(),
+        on_created_empty: rules.on_created_empty.clone(),
+    };
+
+    let workspace = match (workspace, pick) {
+        (Some(workspace), _) => workspace.to_string(),
+        (None, true) => {
+            let monitor = pick_monitor(Clients::get()?, Monitors::get()?, timeout)?;
+            let workspace = Workspaces::get()?
+                .into_iter()
+                .find(|workspace| workspace.id == monitor.active_workspace.id);
+            options.monitor = options.monitor.or(Some(monitor.name));
+            match workspace {
+                Some(workspace) => workspace_selector(&workspace),
+                None => monitor.active_workspace.id.to_string(),
+            }
+        }
+        (None, false) => match tui::workspace::app(Workspaces::get()?.to_vec())? {
+            Some(workspace) => workspace_selector(&workspace),
+            None => return Ok(()),
+        },
+    };
+
+    let rule_text = workspace_rule_text(&workspace, &options)?;
+    let Some(rule_name) = resolve_rule_name(
+        &default_workspace_rule_name(&workspace),
+        naming.name.as_deref(),
+        &naming.on_collision,
+        output,
+    )?
+    else {
+        println!("Nothing was written");
+        return Ok(());
+    };
+    create_workspace_rule_config(&rule_name, &rule_text, output)?;
+    Ok(())
+}
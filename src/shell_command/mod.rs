@@ -1,2 +1,4 @@
 pub mod commands;
+pub mod prompt;
+pub mod rule_name;
 pub mod types;
@@ -0,0 +1,46 @@
+use std::io::{self, IsTerminal, Write};
+
+use color_eyre::{Result, eyre};
+
+/// Ask a yes/no question on the terminal, anything but `y`/`yes` is a no. `flags` are the
+/// command line options that answer it up front, for when there's no terminal to ask on.
+pub fn confirm(question: &str, flags: &str) -> Result<bool> {
+    require_terminal(question, flags)?;
+    print!("{question} [y/N] ");
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Ask the user to pick one of `choices` on the terminal, by its first letter or in full.
+/// Returns `None` when the answer matches none of them. `flags` as for [`confirm`].
+pub fn choose<'c>(question: &str, choices: &[&'c str], flags: &str) -> Result<Option<&'c str>> {
+    require_terminal(question, flags)?;
+    let letters = choices
+        .iter()
+        .filter_map(|choice| choice.chars().next())
+        .map(String::from)
+        .collect::<Vec<String>>()
+        .join("/");
+    print!("{question} [{letters}] ");
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    let answer = answer.trim().to_lowercase();
+    Ok(choices.iter().copied().find(|choice| {
+        !answer.is_empty() && (choice.to_lowercase() == answer || choice.starts_with(&answer))
+    }))
+}
+
+/// Piped or scripted runs would read an empty answer and quietly back out, so fail instead.
+fn require_terminal(question: &str, flags: &str) -> Result<()> {
+    match io::stdin().is_terminal() {
+        true => Ok(()),
+        false => Err(eyre::eyre!(
+            "There's no terminal to ask on, pass {flags} to answer up front: {question}"
+        )),
+    }
+}
@@ -0,0 +1,49 @@
+use color_eyre::Result;
+use std::fs;
+
+use crate::hyprland_config::{RuleOutput, free_rule_name, rule_file_path, validate_rule_name};
+use crate::shell_command::prompt::choose;
+use crate::shell_command::types::NameCollision;
+
+/// The name a new rule file gets: `name` from the command line, or else `default_name`, with
+/// an existing file of that name dealt with the `on_collision` way. `None` if the user backs
+/// out of the collision.
+pub fn resolve_rule_name(
+    default_name: &str,
+    name: Option<&str>,
+    on_collision: &NameCollision,
+    output: RuleOutput,
+) -> Result<Option<String>> {
+    let rule_name = match name {
+        Some(name) => validate_rule_name(name)?,
+        None => default_name.to_string(),
+    };
+    let file_path = rule_file_path(&rule_name)?;
+    if output == RuleOutput::Stdout || !fs::exists(&file_path)? {
+        return Ok(Some(rule_name));
+    }
+
+    let collision = match (on_collision, output) {
+        (NameCollision::Prompt, RuleOutput::DryRun) => {
+            println!("{} already exists", file_path.to_string_lossy());
+            NameCollision::Overwrite
+        }
+        (NameCollision::Prompt, _) => match choose(
+            &format!(
+                "{} already exists. Add a suffix to the name, overwrite it, or cancel?",
+                file_path.to_string_lossy()
+            ),
+            &["suffix", "overwrite", "cancel"],
+            "--on-collision",
+        )? {
+            Some("suffix") => NameCollision::Suffix,
+            Some("overwrite") => NameCollision::Overwrite,
+            _ => return Ok(None),
+        },
+        (collision, _) => collision.clone(),
+    };
+    match collision {
+        NameCollision::Suffix => free_rule_name(&rule_name).map(Some),
+        NameCollision::Overwrite | NameCollision::Prompt => Ok(Some(rule_name)),
+    }
+}
@@ -1,9 +1,11 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(about, long_about = None)]
 pub struct Cli {
-    /// Optional name to operate on
+    /// Name for the rule generate writes, same as `generate --name`
     pub name: Option<String>,
 
     /// Display version info
@@ -17,27 +19,618 @@ pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
-#[derive(Clone, ValueEnum)]
+#[derive(Debug, Clone, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum SelectWindowBy {
     Title,
     Class,
     InitialClass,
     InitialTitle,
+    #[value(name = "xwayland")]
+    #[serde(rename = "xwayland")]
+    XWayland,
+    Floating,
+    Fullscreen,
+    Pinned,
+    /// Whether the window has focus
+    Focus,
+    /// Whether the window is in a group
+    Group,
+    Workspace,
+    /// The window's workspace as a workspace selector
+    OnWorkspace,
+    /// The window's first tag
+    Tag,
+    XdgTag,
+    /// The content type hint, e.g. video
+    Content,
+    /// The internal and client fullscreen modes
+    FullscreenState,
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum RuleSyntax {
+    /// Pick the syntax from the running Hyprland's version
+    Auto,
+    /// `windowrule = float, ^(class)$`
+    V1,
+    /// `windowrulev2 = float, class:^(class)$`
+    V2,
+    /// `windowrule[name] { match:class = ... }`
+    Block,
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum GeometryUnits {
+    /// Pixels from the monitor's top left corner
+    Pixels,
+    /// Percentages of the monitor's size
+    Percent,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApplyMode {
+    /// Leave the running hyprland alone
+    Off,
+    /// Reload the whole hyprland config
+    Reload,
+    /// Send the rule lines with `hyprctl keyword`
+    Keyword,
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum OnConflict {
+    /// Add the new actions to the existing rule
+    Merge,
+    /// Overwrite the existing rule with the new one
+    Replace,
+    /// Leave the existing rule and write the new one next to it
+    Keep,
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum NameCollision {
+    /// Ask what to do
+    Prompt,
+    /// Add -2, -3, ... to the name until it's free
+    Suffix,
+    /// Write over the existing file
+    Overwrite,
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum IdleInhibitMode {
+    None,
+    Always,
+    Focus,
+    Fullscreen,
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum SuppressEvent {
+    Fullscreen,
+    Maximize,
+    Activate,
+    #[value(name = "activatefocus")]
+    ActivateFocus,
+    #[value(name = "fullscreenoutput")]
+    FullscreenOutput,
+}
+
+#[derive(Args)]
+pub struct WindowActionArgs {
+    #[arg(long, help = "add float rule", conflicts_with = "tile")]
+    pub float: bool,
+
+    #[arg(long, help = "add tile rule", conflicts_with = "float")]
+    pub tile: bool,
+
+    #[arg(long, help = "add fullscreen rule")]
+    pub fullscreen: bool,
+
+    #[arg(long, help = "add maximize rule")]
+    pub maximize: bool,
+
+    #[arg(long, help = "add pseudo tiling rule")]
+    pub pseudo: bool,
+
+    #[arg(
+        long,
+        value_name = "W H",
+        help = "add size rule, e.g. \"800 600\" or \"50% 50%\""
+    )]
+    pub size: Option<String>,
+
+    #[arg(long, value_name = "W H", help = "add minsize rule")]
+    pub min_size: Option<String>,
+
+    #[arg(long, value_name = "W H", help = "add maxsize rule")]
+    pub max_size: Option<String>,
+
+    #[arg(
+        long = "move",
+        value_name = "X Y",
+        help = "add move rule, e.g. \"100 100\" or \"cursor -50% -50%\""
+    )]
+    pub move_to: Option<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "pixels",
+        conflicts_with_all = ["tile", "size", "move_to", "center"],
+        help = "add size and move rules from where the window is now, implies --float"
+    )]
+    pub remember_geometry: Option<GeometryUnits>,
+
+    #[arg(long, help = "add center rule")]
+    pub center: bool,
+
+    #[arg(long, help = "add pin rule")]
+    pub pin: bool,
+
+    #[arg(long, help = "add workspace rule")]
+    pub workspace: Option<String>,
+
+    #[arg(
+        long,
+        requires = "workspace",
+        help = "open on the workspace without switching to it"
+    )]
+    pub silent: bool,
+
+    #[arg(long, help = "add monitor rule, by id or name")]
+    pub monitor: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "ACTIVE [INACTIVE [FULLSCREEN]]",
+        help = "add opacity rule"
+    )]
+    pub opacity: Option<String>,
+
+    #[arg(long, help = "add noblur rule")]
+    pub no_blur: bool,
+
+    #[arg(long, help = "add noshadow rule")]
+    pub no_shadow: bool,
+
+    #[arg(long, help = "add noborder rule")]
+    pub no_border: bool,
+
+    #[arg(long, help = "add noanim rule")]
+    pub no_anim: bool,
+
+    #[arg(long, help = "add nodim rule")]
+    pub no_dim: bool,
+
+    #[arg(long, help = "add nofocus rule")]
+    pub no_focus: bool,
+
+    #[arg(long, help = "add noinitialfocus rule")]
+    pub no_initial_focus: bool,
+
+    #[arg(long, help = "add dimaround rule")]
+    pub dim_around: bool,
+
+    #[arg(long, help = "add keepaspectratio rule")]
+    pub keep_aspect_ratio: bool,
+
+    #[arg(long, help = "add rounding rule")]
+    pub rounding: Option<u32>,
+
+    #[arg(long, help = "add bordersize rule")]
+    pub border_size: Option<u32>,
+
+    #[arg(long, help = "add idleinhibit rule")]
+    pub idle_inhibit: Option<IdleInhibitMode>,
+
+    #[arg(long, value_delimiter = ',', help = "add suppressevent rule")]
+    pub suppress_event: Vec<SuppressEvent>,
+
+    #[arg(long, help = "add stayfocused rule")]
+    pub stay_focused: bool,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "add tag rule, prefix the name with - to remove the tag instead"
+    )]
+    pub tag: Option<String>,
+}
+
+#[derive(Args)]
+pub struct SelectArgs {
+    #[arg(long, help = "name of value to use in the windowrule query")]
+    pub select_by: Vec<SelectWindowBy>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        conflicts_with_all = ["select_by", "remember_geometry", "window"],
+        help = "match windows carrying this tag instead of picking a window"
+    )]
+    pub match_tag: Option<String>,
+
+    #[arg(
+        long,
+        group = "window",
+        help = "use the window with this address instead of picking one"
+    )]
+    pub address: Option<String>,
+
+    #[arg(
+        long,
+        group = "window",
+        help = "use the window of this process instead of picking one"
+    )]
+    pub pid: Option<i32>,
+
+    #[arg(
+        long,
+        group = "window",
+        help = "use the focused window instead of picking one"
+    )]
+    pub active: bool,
+
+    #[arg(
+        long = "match",
+        value_name = "FIELD=PATTERN",
+        group = "window",
+        help = "use the one open window matching, e.g. class=kitty, repeat to narrow it down"
+    )]
+    pub match_window: Vec<String>,
+    #[arg(
+        long,
+        conflicts_with_all = ["match_tag", "window"],
+        help = "click several windows, then press enter or right click, and write one rule for them all"
+    )]
+    pub multi: bool,
+
+    #[arg(
+        long,
+        requires = "multi",
+        conflicts_with = "name",
+        help = "with --multi, write a rule for each window instead of one for them all"
+    )]
+    pub rule_per_window: bool,
+
+    #[arg(
+        long,
+        value_parser = humantime::parse_duration,
+        conflicts_with_all = ["match_tag", "window"],
+        help = "give up on picking a window after this long, e.g. 30s"
+    )]
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Args)]
+pub struct RuleFileArgs {
+    #[arg(
+        long,
+        help = "name for the rule file and rule, defaults to the class and a hash of the selector"
+    )]
+    pub name: Option<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value = "prompt",
+        help = "what to do when a rule file with that name already exists"
+    )]
+    pub on_collision: NameCollision,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "what to do with existing rules for the same window, asked for when left out"
+    )]
+    pub on_conflict: Option<OnConflict>,
+
+    #[arg(
+        short,
+        long,
+        help = "add the source line for the rules directory without asking, if it's missing"
+    )]
+    pub yes: bool,
+}
+
+/// Naming for the rule files of workspace and layer rules, like [`RuleFileArgs`] without the
+/// window specific parts.
+#[derive(Args)]
+pub struct RuleNameArgs {
+    #[arg(
+        long,
+        help = "name for the rule file, made up from what the rule is for when left out"
+    )]
+    pub name: Option<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value = "prompt",
+        help = "what to do when a rule file with that name already exists"
+    )]
+    pub on_collision: NameCollision,
+}
+
+#[derive(Args)]
+pub struct LayerActionArgs {
+    #[arg(long, help = "add blur rule")]
+    pub blur: bool,
+
+    #[arg(
+        long,
+        help = "add ignorezero rule, don't blur fully transparent pixels"
+    )]
+    pub ignore_zero: bool,
+
+    #[arg(
+        long,
+        value_name = "ALPHA",
+        conflicts_with = "ignore_zero",
+        help = "add ignorealpha rule, don't blur pixels below this alpha (0 to 1)"
+    )]
+    pub ignore_alpha: Option<f32>,
+
+    #[arg(long, help = "add noanim rule")]
+    pub no_anim: bool,
+
+    #[arg(
+        long,
+        value_name = "STYLE",
+        help = "add animation rule, e.g. \"slide top\" or \"popin 80%\""
+    )]
+    pub animation: Option<String>,
+
+    #[arg(long, help = "add dimaround rule")]
+    pub dim_around: bool,
+
+    #[arg(
+        long,
+        value_name = "0|1",
+        value_parser = clap::value_parser!(u8).range(0..=1),
+        help = "add xray rule"
+    )]
+    pub xray: Option<u8>,
+
+    #[arg(
+        long,
+        allow_hyphen_values = true,
+        help = "add order rule, lower orders are drawn first"
+    )]
+    pub order: Option<i32>,
+}
+
+#[derive(Args)]
+pub struct WorkspaceRuleArgs {
+    #[arg(long, help = "bind the workspace to a monitor, by name")]
+    pub monitor: Option<String>,
+
+    #[arg(long, help = "make it the default workspace of its monitor")]
+    pub default: bool,
+
+    #[arg(long, help = "keep the workspace around even when it's empty")]
+    pub persistent: bool,
+
+    #[arg(long, value_name = "GAPS", help = "gaps between windows")]
+    pub gaps_in: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "GAPS",
+        help = "gaps between windows and monitor edges"
+    )]
+    pub gaps_out: Option<String>,
+
+    #[arg(
+        long = "layoutopt",
+        value_name = "OPTION:VALUE",
+        help = "add a layout option, e.g. orientation:left"
+    )]
+    pub layout_options: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "COMMAND",
+        help = "run a command when the workspace is created empty"
+    )]
+    pub on_created_empty: Option<String>,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     Generate {
-        #[arg(long, help = "add float rule", conflicts_with = "tile")]
-        float: bool,
+        #[command(flatten)]
+        actions: Box<WindowActionArgs>,
+
+        #[command(flatten)]
+        select: SelectArgs,
 
-        #[arg(long, help = "add tile rule", conflicts_with = "float")]
-        tile: bool,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "auto",
+            help = "rule syntax to write"
+        )]
+        syntax: RuleSyntax,
 
-        #[arg(long, help = "add fullscreen rule")]
-        fullscreen: bool,
+        #[arg(
+            long,
+            value_enum,
+            num_args = 0..=1,
+            default_missing_value = "reload",
+            help = "load the rule into the running hyprland and check it took, \
+                    defaults to the config file's apply setting"
+        )]
+        apply: Option<ApplyMode>,
 
-        #[arg(long, help = "name of value to use in the windowrule query")]
-        select_by: Vec<SelectWindowBy>,
+        #[arg(
+            long,
+            conflicts_with = "apply",
+            help = "print the target path and file contents without writing anything"
+        )]
+        dry_run: bool,
+
+        #[arg(
+            long,
+            conflicts_with_all = ["apply", "dry_run"],
+            help = "print only the rule text instead of writing a file"
+        )]
+        stdout: bool,
+
+        #[command(flatten)]
+        files: RuleFileArgs,
     },
+    /// Generate a workspace rule
+    Workspace {
+        #[arg(help = "workspace id or name:NAME, picked from a list when left out")]
+        workspace: Option<String>,
+
+        #[arg(
+            long,
+            conflicts_with = "workspace",
+            help = "click a monitor to write the rule for its active workspace and bind it there"
+        )]
+        pick_monitor: bool,
+
+        #[arg(
+            long,
+            value_parser = humantime::parse_duration,
+            requires = "pick_monitor",
+            help = "give up on picking a monitor after this long, e.g. 30s"
+        )]
+        timeout: Option<Duration>,
+
+        #[command(flatten)]
+        rules: WorkspaceRuleArgs,
+
+        #[command(flatten)]
+        naming: RuleNameArgs,
+
+        #[arg(
+            long,
+            help = "print the target path and file contents without writing anything"
+        )]
+        dry_run: bool,
+
+        #[arg(
+            long,
+            conflicts_with = "dry_run",
+            help = "print only the rule text instead of writing a file"
+        )]
+        stdout: bool,
+    },
+    /// Generate a layer rule for a layer-shell surface such as a bar or launcher
+    Layerrule {
+        #[arg(help = "layer namespace, picked from a list when left out")]
+        namespace: Option<String>,
+
+        #[command(flatten)]
+        actions: LayerActionArgs,
+
+        #[command(flatten)]
+        naming: RuleNameArgs,
+
+        #[arg(
+            long,
+            value_enum,
+            default_value = "auto",
+            help = "rule syntax to write"
+        )]
+        syntax: RuleSyntax,
+
+        #[arg(
+            long,
+            help = "print the target path and file contents without writing anything"
+        )]
+        dry_run: bool,
+
+        #[arg(
+            long,
+            conflicts_with = "dry_run",
+            help = "print only the rule text instead of writing a file"
+        )]
+        stdout: bool,
+    },
+    /// List the rule files written by generate
+    List {
+        #[arg(long, help = "print the rules as JSON")]
+        json: bool,
+    },
+    /// Change the actions or the selector of a generated rule
+    Edit {
+        #[arg(help = "rule file to edit, with or without the .conf extension")]
+        rule: String,
+    },
+    /// Delete generated rule files
+    Remove {
+        #[arg(help = "rule file to remove, with or without the .conf extension")]
+        file: Option<String>,
+
+        #[arg(long, help = "remove rules whose class selector matches this class")]
+        class: Option<String>,
+
+        #[arg(long, help = "remove rules whose title selector matches this title")]
+        title: Option<String>,
+
+        #[arg(
+            long,
+            conflicts_with_all = ["file", "class", "title"],
+            help = "click a window to remove the rules that apply to it"
+        )]
+        pick: bool,
+
+        #[arg(
+            long,
+            value_parser = humantime::parse_duration,
+            requires = "pick",
+            help = "give up on picking a window after this long, e.g. 30s"
+        )]
+        timeout: Option<Duration>,
+
+        #[arg(short, long, help = "don't ask for confirmation")]
+        yes: bool,
+
+        #[arg(long, help = "reload hyprland once the files are gone")]
+        reload: bool,
+
+        #[arg(
+            long,
+            conflicts_with = "reload",
+            help = "don't reload hyprland, even when the config file says to"
+        )]
+        no_reload: bool,
+    },
+    /// Check that hyprland.conf sources the rules directory, and offer to add the line if not
+    SourceCheck {
+        #[arg(short, long, help = "add the source line without asking")]
+        yes: bool,
+    },
+    /// List the changes made to rule files and hyprland.conf, newest first
+    History {
+        #[arg(long, help = "print the changes as JSON")]
+        json: bool,
+    },
+    /// Put the files back the way they were before the latest changes
+    Undo {
+        #[arg(default_value_t = 1, help = "how many of the latest changes to undo")]
+        count: usize,
+
+        #[arg(short, long, help = "don't ask for confirmation")]
+        yes: bool,
+    },
+    /// Settings for this tool, read from config.toml in its config directory
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Print the settings in effect, the config file's merged over the defaults
+    Show,
 }
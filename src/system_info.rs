@@ -3,27 +3,29 @@ use std::path::{Path, PathBuf};
 use color_eyre::eyre::{self, WrapErr};
 use directories::{ProjectDirs, UserDirs};
 
+use crate::config;
+
 pub fn get_data_dir() -> eyre::Result<PathBuf> {
-    let directory = if let Ok(s) = std::env::var("RATATUI_TEMPLATE_DATA") {
+    let directory = if let Ok(s) = std::env::var("HYPRLAND_WINDOW_RULE_DATA") {
         PathBuf::from(s)
-    } else if let Some(proj_dirs) = ProjectDirs::from("", "", "ratatui-template") {
+    } else if let Some(proj_dirs) = ProjectDirs::from("", "", "hyprland-window-rule") {
         proj_dirs.data_local_dir().to_path_buf()
     } else {
         return Err(eyre::eyre!(
-            "Unable to find data directory for ratatui-template"
+            "Unable to find data directory for hyprland-window-rule"
         ));
     };
     Ok(directory)
 }
 
 pub fn get_config_dir() -> eyre::Result<PathBuf> {
-    let directory = if let Ok(s) = std::env::var("RATATUI_TEMPLATE_CONFIG") {
+    let directory = if let Ok(s) = std::env::var("HYPRLAND_WINDOW_RULE_CONFIG") {
         PathBuf::from(s)
-    } else if let Some(proj_dirs) = ProjectDirs::from("", "", "ratatui-template") {
+    } else if let Some(proj_dirs) = ProjectDirs::from("", "", "hyprland-window-rule") {
         proj_dirs.config_local_dir().to_path_buf()
     } else {
         return Err(eyre::eyre!(
-            "Unable to find config directory for ratatui-template"
+            "Unable to find config directory for hyprland-window-rule"
         ));
     };
     Ok(directory)
@@ -35,9 +37,7 @@ pub fn get_hyprland_dir() -> eyre::Result<PathBuf> {
     } else if let Some(proj_dirs) = ProjectDirs::from("", "", "hypr") {
         proj_dirs.config_local_dir().to_path_buf()
     } else {
-        return Err(eyre::eyre!(
-            "Unable to find data directory for ratatui-template"
-        ));
+        return Err(eyre::eyre!("Unable to find the hyprland config directory"));
     };
     Ok(directory)
 }
@@ -48,7 +48,7 @@ pub fn get_window_rules_dir() -> eyre::Result<PathBuf> {
             let directory = if let Ok(var_dir) = std::env::var("WINDOW_RULE_DIR") {
                 Path::join(&hyprland_dir, var_dir)
             } else {
-                Path::join(&hyprland_dir, "window-rules/")
+                Path::join(&hyprland_dir, &config::get().rules_dir)
             };
             Ok(directory)
         }
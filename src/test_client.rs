@@ -0,0 +1,32 @@
+//! A window to hand the functions that take Hyprland's clients in tests.
+
+use hyprland::data::{Client, FullscreenMode, WorkspaceBasic};
+use hyprland::shared::Address;
+
+/// A mapped, tiled 100x100 window at the origin of workspace 1 on monitor 0.
+pub fn client(address: &str, class: &str, title: &str) -> Client {
+    Client {
+        address: Address::new(address),
+        at: (0, 0),
+        size: (100, 100),
+        workspace: WorkspaceBasic {
+            id: 1,
+            name: "1".to_string(),
+        },
+        floating: false,
+        fullscreen: FullscreenMode::None,
+        fullscreen_client: FullscreenMode::None,
+        monitor: Some(0),
+        initial_class: class.to_string(),
+        class: class.to_string(),
+        initial_title: title.to_string(),
+        title: title.to_string(),
+        pid: 1,
+        xwayland: false,
+        pinned: false,
+        grouped: vec![],
+        mapped: true,
+        swallowing: None,
+        focus_history_id: 1,
+    }
+}
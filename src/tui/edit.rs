@@ -0,0 +1,171 @@
+use super::root::tui;
+use super::types::{RuleOptionsState, SelectorState};
+use super::widgets::rule_options::rule_options;
+use super::widgets::selector::selector;
+
+use crate::hyprland_config::{ClientExtras, SelectWindowBy, WindowOptions};
+
+use color_eyre::{Result, eyre};
+use hyprland::data::Client;
+use ratatui::Frame;
+use ratatui::crossterm::event;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use std::time::Duration;
+
+#[derive(Debug, Default, PartialEq)]
+enum PageState {
+    #[default]
+    RuleOptions,
+    Selector,
+    Done,
+}
+
+struct Model {
+    title: String,
+    page: PageState,
+    rule_options: RuleOptionsState,
+    selector: SelectorState,
+    edited: Option<RuleEdit>,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct RuleEdit {
+    pub window_options: WindowOptions,
+    pub matchers: Vec<(SelectWindowBy, String)>,
+}
+
+enum Messages {
+    Quit,
+    MoveCursor(isize),
+    Toggle,
+    Confirm,
+    Input(char),
+    Backspace,
+    FinishEditing,
+    ClearValue,
+}
+
+/// Walk through the actions and the selector of an existing rule, `title` names the rule in
+/// the page headers. `client` is an open window the rule matches along with its extras, if
+/// there is one.
+pub fn app(
+    title: &str,
+    rules: &[String],
+    matchers: &[(SelectWindowBy, String)],
+    client: Option<(Client, ClientExtras)>,
+) -> Result<Option<RuleEdit>> {
+    let rule_options = RuleOptionsState::with_rules(rules).map_err(|error| eyre::eyre!(error))?;
+    let mut model = Model {
+        title: title.to_string(),
+        page: PageState::default(),
+        rule_options,
+        selector: SelectorState::new(matchers, client),
+        edited: None,
+    };
+
+    tui::install_panic_hook();
+    let mut terminal = tui::init_terminal()?;
+    while model.page != PageState::Done {
+        terminal.draw(|frame| view(&model, frame))?;
+        if let Some(message) = handle_event(&model)? {
+            update(&mut model, message);
+        }
+    }
+    tui::restore_terminal()?;
+
+    Ok(model.edited)
+}
+
+fn view(model: &Model, frame: &mut Frame) {
+    match model.page {
+        PageState::RuleOptions => rule_options(&model.title, &model.rule_options, frame),
+        PageState::Selector => selector(&model.title, &model.selector, frame),
+        PageState::Done => {}
+    }
+}
+
+fn update(model: &mut Model, message: Messages) {
+    match (&model.page, message) {
+        (_, Messages::Quit) => model.page = PageState::Done,
+        (PageState::RuleOptions, Messages::MoveCursor(offset)) => {
+            model.rule_options.move_cursor(offset)
+        }
+        (PageState::RuleOptions, Messages::Toggle) => model.rule_options.toggle(),
+        (PageState::RuleOptions, Messages::Input(character)) => model.rule_options.input(character),
+        (PageState::RuleOptions, Messages::Backspace) => model.rule_options.backspace(),
+        (PageState::RuleOptions, Messages::FinishEditing) => model.rule_options.finish_editing(),
+        (PageState::RuleOptions, Messages::ClearValue) => {
+            model.rule_options.options[model.rule_options.cursor]
+                .value
+                .clear();
+            model.rule_options.finish_editing();
+        }
+        (PageState::RuleOptions, Messages::Confirm) => match model.rule_options.window_options() {
+            Ok(_) => model.page = PageState::Selector,
+            Err(error) => model.rule_options.error = Some(error),
+        },
+        (PageState::Selector, Messages::MoveCursor(offset)) => model.selector.move_cursor(offset),
+        (PageState::Selector, Messages::Toggle) => model.selector.start_editing(),
+        (PageState::Selector, Messages::Input(character)) => model.selector.input(character),
+        (PageState::Selector, Messages::Backspace) => model.selector.backspace(),
+        (PageState::Selector, Messages::FinishEditing) => model.selector.finish_editing(),
+        (PageState::Selector, Messages::ClearValue) => {
+            model.selector.options[model.selector.cursor]
+                .pattern
+                .clear();
+            model.selector.finish_editing();
+        }
+        (PageState::Selector, Messages::Confirm) => {
+            match (
+                model.rule_options.window_options(),
+                model.selector.matchers(),
+            ) {
+                (Ok(window_options), Ok(matchers)) => {
+                    model.edited = Some(RuleEdit {
+                        window_options,
+                        matchers,
+                    });
+                    model.page = PageState::Done;
+                }
+                (Err(error), _) | (_, Err(error)) => model.selector.error = Some(error),
+            }
+        }
+        (PageState::Done, _) => {}
+    }
+}
+
+fn handle_event(model: &Model) -> Result<Option<Messages>> {
+    if event::poll(Duration::from_millis(16))?
+        && let Event::Key(key) = event::read()?
+        && key.kind == event::KeyEventKind::Press
+    {
+        return Ok(handle_key(model, key));
+    }
+    Ok(None)
+}
+
+fn handle_key(model: &Model, key: event::KeyEvent) -> Option<Messages> {
+    let editing = match model.page {
+        PageState::RuleOptions => model.rule_options.editing,
+        PageState::Selector => model.selector.editing,
+        PageState::Done => false,
+    };
+    if editing {
+        return match key.code {
+            KeyCode::Enter => Some(Messages::FinishEditing),
+            KeyCode::Esc => Some(Messages::ClearValue),
+            KeyCode::Backspace => Some(Messages::Backspace),
+            KeyCode::Char(character) => Some(Messages::Input(character)),
+            _ => None,
+        };
+    }
+    match key.code {
+        KeyCode::Char('q') => Some(Messages::Quit),
+        KeyCode::Up | KeyCode::Char('k') => Some(Messages::MoveCursor(-1)),
+        KeyCode::Down | KeyCode::Char('j') => Some(Messages::MoveCursor(1)),
+        KeyCode::Char(' ') => Some(Messages::Toggle),
+        KeyCode::Enter => Some(Messages::Confirm),
+        _ => None,
+    }
+}
@@ -0,0 +1,160 @@
+use super::root::tui;
+use super::types::RuleOptionsState;
+use super::widgets::layer_list::layer_list;
+use super::widgets::rule_options::rule_options;
+
+use crate::hyprland_config::{LayerAction, LayerSurface};
+
+use color_eyre::Result;
+use ratatui::Frame;
+use ratatui::crossterm::event;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use std::time::Duration;
+
+#[derive(Debug, Default, PartialEq)]
+enum PageState {
+    #[default]
+    Layers,
+    RuleOptions,
+    Done,
+}
+
+struct Model {
+    layers: Vec<LayerSurface>,
+    cursor: usize,
+    page: PageState,
+    rule_options: Option<RuleOptionsState>,
+    selected: Option<LayerRule>,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct LayerRule {
+    pub namespace: String,
+    /// Empty when the actions came from the command line
+    pub actions: Vec<LayerAction>,
+}
+
+enum Messages {
+    Quit,
+    MoveCursor(isize),
+    Toggle,
+    Confirm,
+    Input(char),
+    Backspace,
+    FinishEditing,
+    ClearValue,
+}
+
+/// Pick a layer namespace from `layers`, then its actions when `prompt_actions` is set.
+pub fn app(layers: Vec<LayerSurface>, prompt_actions: bool) -> Result<Option<LayerRule>> {
+    if layers.is_empty() {
+        return Ok(None);
+    }
+
+    let mut model = Model {
+        layers,
+        cursor: 0,
+        page: PageState::default(),
+        rule_options: prompt_actions.then(RuleOptionsState::for_layers),
+        selected: None,
+    };
+
+    tui::install_panic_hook();
+    let mut terminal = tui::init_terminal()?;
+    while model.page != PageState::Done {
+        terminal.draw(|frame| view(&model, frame))?;
+        if let Some(message) = handle_event(&model)? {
+            update(&mut model, message);
+        }
+    }
+    tui::restore_terminal()?;
+
+    Ok(model.selected)
+}
+
+fn view(model: &Model, frame: &mut Frame) {
+    match (&model.page, &model.rule_options) {
+        (PageState::Layers, _) => layer_list(&model.layers, model.cursor, frame),
+        (PageState::RuleOptions, Some(state)) => {
+            rule_options(&model.layers[model.cursor].namespace, state, frame)
+        }
+        (PageState::RuleOptions, None) | (PageState::Done, _) => {}
+    }
+}
+
+fn update(model: &mut Model, message: Messages) {
+    let namespace = model.layers[model.cursor].namespace.clone();
+    match (&model.page, model.rule_options.as_mut(), message) {
+        (_, _, Messages::Quit) => model.page = PageState::Done,
+        (PageState::Layers, _, Messages::MoveCursor(offset)) => {
+            model.cursor = model
+                .cursor
+                .saturating_add_signed(offset)
+                .min(model.layers.len() - 1);
+        }
+        (PageState::Layers, Some(_), Messages::Confirm) => model.page = PageState::RuleOptions,
+        (PageState::Layers, None, Messages::Confirm) => {
+            model.selected = Some(LayerRule {
+                namespace,
+                actions: Vec::new(),
+            });
+            model.page = PageState::Done;
+        }
+        (PageState::RuleOptions, Some(state), message) => match message {
+            Messages::MoveCursor(offset) => state.move_cursor(offset),
+            Messages::Toggle => state.toggle(),
+            Messages::Input(character) => state.input(character),
+            Messages::Backspace => state.backspace(),
+            Messages::FinishEditing => state.finish_editing(),
+            Messages::ClearValue => {
+                state.options[state.cursor].value.clear();
+                state.finish_editing();
+            }
+            Messages::Confirm => match state.layer_actions() {
+                Ok(actions) => {
+                    model.selected = Some(LayerRule { namespace, actions });
+                    model.page = PageState::Done;
+                }
+                Err(error) => state.error = Some(error),
+            },
+            Messages::Quit => {}
+        },
+        _ => {}
+    }
+}
+
+fn handle_event(model: &Model) -> Result<Option<Messages>> {
+    if event::poll(Duration::from_millis(16))?
+        && let Event::Key(key) = event::read()?
+        && key.kind == event::KeyEventKind::Press
+    {
+        return Ok(handle_key(model, key));
+    }
+    Ok(None)
+}
+
+fn handle_key(model: &Model, key: event::KeyEvent) -> Option<Messages> {
+    let editing = model.page == PageState::RuleOptions
+        && model
+            .rule_options
+            .as_ref()
+            .is_some_and(|state| state.editing);
+    if editing {
+        return match key.code {
+            KeyCode::Enter => Some(Messages::FinishEditing),
+            KeyCode::Esc => Some(Messages::ClearValue),
+            KeyCode::Backspace => Some(Messages::Backspace),
+            KeyCode::Char(character) => Some(Messages::Input(character)),
+            _ => None,
+        };
+    }
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => Some(Messages::Quit),
+        KeyCode::Up | KeyCode::Char('k') => Some(Messages::MoveCursor(-1)),
+        KeyCode::Down | KeyCode::Char('j') => Some(Messages::MoveCursor(1)),
+        KeyCode::Char(' ') => Some(Messages::Toggle),
+        KeyCode::Enter => Some(Messages::Confirm),
+        _ => None,
+    }
+}
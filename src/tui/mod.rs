@@ -1,2 +1,6 @@
+pub mod edit;
+pub mod layer;
 pub mod root;
+mod types;
 pub(self) mod widgets;
+pub mod workspace;
@@ -1,25 +1,34 @@
+use super::types::{RuleOptionsState, SelectByState};
+use super::widgets::rule_options::rule_options;
+use super::widgets::select_by::select_by;
 use super::widgets::window_select::select_window;
 
+use crate::config::ClickAction;
 use crate::hyprland_config::SelectWindowBy;
 use crate::hyprland_config::WindowOptions;
+use crate::hyprland_config::{ClientExtras, client_extras, combined_matchers, matching_clients};
 use crate::wayland;
-use crate::wayland::window_selector::Message;
+use crate::wayland::window_selector::{Button, Click, Message, Selection, Unpicked};
 
 use hyprland::data::*;
 use hyprland::prelude::*;
+use hyprland::shared::Address;
 use ratatui::crossterm::event;
 use ratatui::crossterm::event::Event;
 use ratatui::crossterm::event::KeyCode;
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::time::Duration;
 
-use color_eyre::Result;
+use color_eyre::{Result, eyre};
 use ratatui::Frame;
 
 #[derive(Debug, Default, PartialEq)]
 enum PageState {
     #[default]
     WindowSelect,
+    RuleOptions,
+    SelectBy,
 }
 
 pub enum OrPrompt<T> {
@@ -29,19 +38,44 @@ pub enum OrPrompt<T> {
 
 struct Model {
     hovered_client: Option<Client>,
+    /// Tags, xdg tag and content type of the hovered client
+    hovered_extras: ClientExtras,
+    /// Open windows the rule for the hovered client would also apply to, itself included, or
+    /// why it can't match any
+    matching_clients: Result<Vec<Client>, String>,
+    /// Every open window and their extras as of the last hover, to preview the rule against
+    open_clients: Vec<Client>,
+    open_extras: HashMap<String, ClientExtras>,
     running_state: RunningState,
     page: PageState,
     window_options: OrPrompt<WindowOptions>,
     select_by_list: OrPrompt<Rc<Vec<SelectWindowBy>>>,
+    rule_options: RuleOptionsState,
+    select_by: SelectByState,
+    /// Clicks build up `selected` instead of picking the hovered window
+    multi_select: bool,
+    /// Windows picked in multi-select mode, in the order they were clicked
+    selected: Vec<(Client, ClientExtras)>,
+    clicks: ClickBindings,
+    /// Shift is held on the overlay, so a click would match on the Shift properties
+    shift_held: bool,
 }
 
 impl Model {
     fn new(
         window_options: Option<WindowOptions>,
         select_by_list: Option<Rc<Vec<SelectWindowBy>>>,
-    ) -> Self {
-        return Self {
+        defaults: PromptDefaults,
+        multi_select: bool,
+    ) -> Result<Self> {
+        let rule_options =
+            RuleOptionsState::with_rules(&defaults.actions).map_err(|error| eyre::eyre!(error))?;
+        return Ok(Self {
             hovered_client: None,
+            hovered_extras: ClientExtras::default(),
+            matching_clients: Ok(vec![]),
+            open_clients: vec![],
+            open_extras: HashMap::new(),
             running_state: RunningState::default(),
             page: PageState::default(),
             window_options: match window_options {
@@ -52,7 +86,134 @@ impl Model {
                 Some(select_by_list) => OrPrompt::Args(select_by_list),
                 None => OrPrompt::Prompt,
             },
+            rule_options,
+            select_by: SelectByState::new(&defaults.select_by_list),
+            multi_select,
+            selected: vec![],
+            clicks: defaults.clicks,
+            shift_held: false,
+        });
+    }
+
+    /// End a multi-select, the pages show the first window picked.
+    fn finish_selection(&mut self) {
+        match self.selected.first() {
+            Some((client, extras)) => {
+                self.hovered_client = Some(client.clone());
+                self.hovered_extras = extras.clone();
+                self.next_page();
+                self.refresh_preview();
+            }
+            None => self.running_state = RunningState::Unpicked(Unpicked::Nothing),
+        }
+    }
+
+    /// Settle whatever the click's button decides and the command line left open, so a button
+    /// with actions skips the pages.
+    fn apply_click(&mut self, click: Click) {
+        let Some(ClickAction::Actions(actions)) = self.clicks.action(click.button) else {
+            return;
         };
+        // The config file's actions were checked when it was read
+        if let OrPrompt::Prompt = self.window_options
+            && let Ok(window_options) = WindowOptions::from_rules(actions)
+        {
+            self.window_options = OrPrompt::Args(window_options);
+        }
+        if let OrPrompt::Prompt = self.select_by_list {
+            let select_by_list = self.clicks.select_by_list(click.shift).to_vec();
+            self.select_by_list = OrPrompt::Args(Rc::new(select_by_list));
+        }
+    }
+
+    /// What each button does, for the footer of the window select page.
+    fn click_hint(&self) -> String {
+        let describe = |button| match self.clicks.action(button) {
+            Some(ClickAction::Actions(actions)) if actions.is_empty() => "no actions".to_string(),
+            Some(ClickAction::Actions(actions)) => actions.join(", "),
+            Some(ClickAction::Prompt(_)) | None => "options".to_string(),
+        };
+        let labels = |select_by_list: &[SelectWindowBy]| {
+            select_by_list
+                .iter()
+                .map(|select_by| select_by.label().to_lowercase())
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        let matching = match (&self.select_by_list, self.shift_held) {
+            (OrPrompt::Args(select_by_list), _) => labels(select_by_list),
+            (OrPrompt::Prompt, true) => labels(&self.clicks.shift_select_by_list),
+            (OrPrompt::Prompt, false) => format!(
+                "{} (hold shift for {})",
+                labels(&self.clicks.select_by_list),
+                labels(&self.clicks.shift_select_by_list)
+            ),
+        };
+        format!(
+            "left click: {}  right click: {}  middle click: {}  matching {matching}",
+            describe(Button::Left),
+            describe(Button::Right),
+            describe(Button::Middle),
+        )
+    }
+
+    /// What the rule is being written for, in the pages' headings.
+    fn subject(&self, client: &Client) -> String {
+        match self.selected.len() {
+            0 | 1 => client.title.clone(),
+            count => format!("{count} windows"),
+        }
+    }
+
+    /// Move on to the first page that still has something to ask, or finish.
+    fn next_page(&mut self) {
+        self.page = match (&self.window_options, &self.select_by_list) {
+            (OrPrompt::Prompt, _) => PageState::RuleOptions,
+            (_, OrPrompt::Prompt) => PageState::SelectBy,
+            _ => {
+                self.running_state = RunningState::Done;
+                return;
+            }
+        };
+    }
+
+    /// The properties the rule will match on: a left click's when it writes the rule straight
+    /// away from the window select page, otherwise whatever is ticked on the select-by page.
+    fn preview_select_by_list(&self) -> Result<Vec<SelectWindowBy>, String> {
+        match (&self.select_by_list, self.clicks.action(Button::Left)) {
+            (OrPrompt::Args(select_by_list), _) => Ok(select_by_list.to_vec()),
+            (OrPrompt::Prompt, Some(ClickAction::Actions(_)))
+                if !self.multi_select && self.page == PageState::WindowSelect =>
+            {
+                Ok(self.clicks.select_by_list(self.shift_held).to_vec())
+            }
+            (OrPrompt::Prompt, _) => self.select_by.select_by_list(),
+        }
+    }
+
+    /// Work out which open windows the rule would match: the hovered window's while picking,
+    /// afterwards the rule for every window picked.
+    fn refresh_preview(&mut self) {
+        let Some(client) = &self.hovered_client else {
+            return;
+        };
+        let windows = match (&self.page, self.selected.as_slice()) {
+            (PageState::WindowSelect, _) | (_, []) => vec![(client, &self.hovered_extras)],
+            (_, selected) => selected
+                .iter()
+                .map(|(client, extras)| (client, extras))
+                .collect(),
+        };
+        self.matching_clients = self.preview_select_by_list().and_then(|select_by_list| {
+            let matchers =
+                combined_matchers(&windows, &select_by_list).map_err(|error| error.to_string())?;
+            Ok(
+                matching_clients(&matchers, &self.open_clients, &self.open_extras)
+                    .into_iter()
+                    .cloned()
+                    .collect(),
+            )
+        });
     }
 }
 
@@ -61,11 +222,51 @@ enum RunningState {
     #[default]
     Running,
     Done,
+    /// Ended without a window to write the rule for
+    Unpicked(Unpicked),
+}
+
+/// What the prompt pages start out with, from the config file.
+pub struct PromptDefaults {
+    /// Rule text of the actions switched on
+    pub actions: Vec<String>,
+    pub select_by_list: Vec<SelectWindowBy>,
+    pub clicks: ClickBindings,
+}
+
+/// What clicking a window does with each button, see [`crate::config::Clicks`].
+pub struct ClickBindings {
+    pub left: ClickAction,
+    pub right: ClickAction,
+    pub middle: ClickAction,
+    pub select_by_list: Vec<SelectWindowBy>,
+    /// Matched on instead when Shift is held
+    pub shift_select_by_list: Vec<SelectWindowBy>,
+}
+
+impl ClickBindings {
+    /// `None` for the buttons there's no binding for.
+    fn action(&self, button: Button) -> Option<&ClickAction> {
+        match button {
+            Button::Left => Some(&self.left),
+            Button::Right => Some(&self.right),
+            Button::Middle => Some(&self.middle),
+            Button::Other => None,
+        }
+    }
+
+    fn select_by_list(&self, shift: bool) -> &[SelectWindowBy] {
+        match shift {
+            true => &self.shift_select_by_list,
+            false => &self.select_by_list,
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Debug)]
 pub struct WindowSelection {
-    pub client: Client,
+    /// The picked window, or every window picked in multi-select mode
+    pub windows: Vec<(Client, ClientExtras)>,
     pub window_options: WindowOptions,
     pub select_by_list: Rc<Vec<SelectWindowBy>>,
 }
@@ -73,122 +274,316 @@ pub struct WindowSelection {
 pub fn app(
     window_options: Option<WindowOptions>,
     select_by_list: Option<Rc<Vec<SelectWindowBy>>>,
-) -> Result<Option<WindowSelection>> {
+    defaults: PromptDefaults,
+    multi_select: bool,
+    timeout: Option<Duration>,
+) -> Result<WindowSelection> {
     tui::install_panic_hook();
     color_eyre::install()?;
 
     let monitors = Monitors::get()?;
     let clients = Clients::get()?;
 
-    let selection_result = render(clients, monitors, window_options, select_by_list);
+    let selection_result = render(
+        clients,
+        monitors,
+        window_options,
+        select_by_list,
+        defaults,
+        multi_select,
+        timeout,
+    );
 
     tui::restore_terminal()?;
     return selection_result;
 }
 
 fn view(model: &mut Model, frame: &mut Frame) {
-    match &model.page {
-        PageState::WindowSelect => select_window(&model.hovered_client, frame),
+    match (&model.page, &model.hovered_client) {
+        (PageState::WindowSelect, _) => select_window(
+            &model.hovered_client,
+            &model.hovered_extras,
+            &model.matching_clients,
+            model.multi_select.then_some(model.selected.as_slice()),
+            // Clicks only toggle windows in multi-select mode
+            (!model.multi_select).then(|| model.click_hint()).as_deref(),
+            frame,
+        ),
+        (PageState::RuleOptions, Some(client)) => {
+            rule_options(&model.subject(client), &model.rule_options, frame)
+        }
+        (PageState::SelectBy, Some(client)) => select_by(
+            client,
+            &model.hovered_extras,
+            &model.select_by,
+            &model.matching_clients,
+            frame,
+        ),
+        (_, None) => {}
     }
 }
 
 enum Messages {
-    ClientUpdate(Client),
+    /// The hovered client along with every open client and their extras, to preview what the
+    /// rule matches
+    ClientUpdate(Client, Vec<Client>, HashMap<String, ClientExtras>),
+    /// The windows picked so far in multi-select mode, with their extras
+    SelectionUpdate(Vec<(Client, ClientExtras)>),
     RunningState(RunningState),
+    MoveCursor(isize),
+    Toggle,
+    Confirm,
+    Input(char),
+    Backspace,
+    FinishEditing,
+    ClearValue,
 }
 
 fn update(model: &mut Model, message: Option<Messages>) -> Option<()> {
     if let Some(message) = message {
         match message {
-            Messages::ClientUpdate(client) => {
-                model.hovered_client = Some(client.clone());
+            Messages::ClientUpdate(client, clients, extras) => {
+                let hovered_extras = extras
+                    .get(&client.address.to_string())
+                    .cloned()
+                    .unwrap_or_default();
+                model.rule_options.known_tags = known_tags(&hovered_extras, &extras);
+                model.hovered_client = Some(client);
+                model.hovered_extras = hovered_extras;
+                model.open_clients = clients;
+                model.open_extras = extras;
+            }
+            Messages::SelectionUpdate(selected) => {
+                model.selected = selected;
             }
             Messages::RunningState(running_state) => {
                 model.running_state = running_state;
             }
+            Messages::MoveCursor(offset) => match model.page {
+                PageState::RuleOptions => model.rule_options.move_cursor(offset),
+                PageState::SelectBy => model.select_by.move_cursor(offset),
+                PageState::WindowSelect => {}
+            },
+            Messages::Toggle => match model.page {
+                PageState::RuleOptions => model.rule_options.toggle(),
+                PageState::SelectBy => model.select_by.toggle(),
+                PageState::WindowSelect => {}
+            },
+            Messages::Confirm => match model.page {
+                PageState::RuleOptions => match model.rule_options.window_options() {
+                    Ok(window_options) => {
+                        model.window_options = OrPrompt::Args(window_options);
+                        model.next_page();
+                    }
+                    Err(error) => model.rule_options.error = Some(error),
+                },
+                PageState::SelectBy => match model.select_by.select_by_list() {
+                    Ok(select_by_list) => {
+                        model.select_by_list = OrPrompt::Args(Rc::new(select_by_list));
+                        model.next_page();
+                    }
+                    Err(error) => model.select_by.error = Some(error),
+                },
+                PageState::WindowSelect if model.multi_select => model.finish_selection(),
+                PageState::WindowSelect => {}
+            },
+            Messages::Input(character) => model.rule_options.input(character),
+            Messages::Backspace => model.rule_options.backspace(),
+            Messages::FinishEditing => model.rule_options.finish_editing(),
+            Messages::ClearValue => {
+                model.rule_options.options[model.rule_options.cursor]
+                    .value
+                    .clear();
+                model.rule_options.finish_editing();
+            }
         };
+        // Ticking a property on the select-by page or hovering another window changes it
+        model.refresh_preview();
     }
     None
 }
 
+/// Every tag on an open window, the hovered window's own tags first.
+fn known_tags(hovered: &ClientExtras, extras: &HashMap<String, ClientExtras>) -> Vec<String> {
+    let mut others = extras
+        .values()
+        .flat_map(|extras| extras.tag_names())
+        .filter(|tag| !hovered.tag_names().any(|own| own == *tag))
+        .map(String::from)
+        .collect::<Vec<String>>();
+    others.sort();
+    others.dedup();
+    hovered
+        .tag_names()
+        .map(String::from)
+        .chain(others)
+        .collect()
+}
+
 fn render(
     clients: Clients,
     monitors: Monitors,
     window_options: Option<WindowOptions>,
     select_by_list: Option<Rc<Vec<SelectWindowBy>>>,
-) -> Result<Option<WindowSelection>> {
-    let mut model = Model::new(window_options, select_by_list);
+    defaults: PromptDefaults,
+    multi_select: bool,
+    timeout: Option<Duration>,
+) -> Result<WindowSelection> {
+    let mut model = Model::new(window_options, select_by_list, defaults, multi_select)?;
 
     let mut terminal = tui::init_terminal().expect("unable to create terminal ui");
-    let mut window_select = wayland::window_selector::WindowSelect::new(clients, monitors);
+    let mut window_select =
+        wayland::window_selector::WindowSelect::new(clients, monitors).timeout(timeout);
+    if multi_select {
+        window_select = window_select.multi_select();
+    }
 
     while model.running_state == RunningState::Running {
         if model.page == PageState::WindowSelect {
-            match window_select.update() {
-                Message::Done => {
+            let message = window_select.update();
+            if window_select.shift_held() != model.shift_held {
+                model.shift_held = window_select.shift_held();
+                model.refresh_preview();
+            }
+            if multi_select
+                && let Some(selected) = selection_update(&model.selected, window_select.selected())
+            {
+                update(&mut model, Some(selected));
+            }
+            match message {
+                Message::Done(selection) => {
                     window_select.clean_up();
-                    if let (OrPrompt::Args(_), OrPrompt::Args(_), Some(_)) = (
-                        &model.select_by_list,
-                        &model.window_options,
-                        &model.hovered_client,
-                    ) {
-                        update(&mut model, Some(Messages::RunningState(RunningState::Done)));
-                    };
-                }
-                Message::HoveredClient(maybe_id) => {
-                    if let Some(id) = maybe_id {
-                        if let Ok(clients) = Clients::get() {
-                            if let Some(client) = clients
-                                .iter()
-                                .find(|client| client.address.to_string() == id.to_string())
-                            {
-                                update(&mut model, Some(Messages::ClientUpdate(client.clone())));
+                    match (selection, multi_select) {
+                        (Selection::Confirmed(..), true) => model.finish_selection(),
+                        (Selection::Confirmed(client_ids, click), false) => {
+                            // Enter can land in the same dispatch as a keyboard move, before
+                            // the model has heard of the window
+                            if let Some(id) = client_ids.first() {
+                                update(&mut model, client_update(id));
                             }
+                            if let Some(click) = click {
+                                model.apply_click(click);
+                            }
+                            model.next_page();
                         }
-                    }
+                        (Selection::Unpicked(unpicked), _) => {
+                            let message = Messages::RunningState(RunningState::Unpicked(unpicked));
+                            update(&mut model, Some(message));
+                        }
+                    };
+                }
+                Message::HoveredClient(Some(id)) => {
+                    update(&mut model, client_update(&id));
                 }
+                Message::HoveredClient(None) | Message::Idle => {}
             }
         }
 
         if let Ok(current_update) = handle_event(&model) {
             update(&mut model, current_update);
         }
+        // Enter in the terminal can end a multi-select before the overlay does
+        if model.page != PageState::WindowSelect {
+            window_select.clean_up();
+        }
 
         terminal.draw(|f| view(&mut model, f))?;
     }
+    if let RunningState::Unpicked(unpicked) = model.running_state {
+        return Err(unpicked.into());
+    }
     if let (OrPrompt::Args(select_by_list), OrPrompt::Args(window_options), Some(client)) = (
         model.select_by_list,
         model.window_options,
         model.hovered_client,
     ) {
-        return Ok(Some(WindowSelection {
-            client,
+        let windows = match multi_select {
+            true => model.selected,
+            false => vec![(client, model.hovered_extras)],
+        };
+        return Ok(WindowSelection {
+            windows,
             window_options: window_options,
             select_by_list: select_by_list,
-        }));
+        });
     };
-    Ok(None)
+    Err(Unpicked::Nothing.into())
 }
 
-fn handle_event(_: &Model) -> color_eyre::Result<Option<Messages>> {
+/// The window the overlay reports as hovered, along with everything open, to preview what the
+/// rule matches.
+fn client_update(id: &Address) -> Option<Messages> {
+    let clients = Clients::get().ok()?;
+    let client = clients.iter().find(|client| client.address == *id)?;
+    Some(Messages::ClientUpdate(
+        client.clone(),
+        clients.to_vec(),
+        client_extras().unwrap_or_default(),
+    ))
+}
+
+/// The picked windows when `selected`, the addresses the overlay reports, no longer lines up
+/// with the ones in the model.
+fn selection_update(
+    current: &[(Client, ClientExtras)],
+    selected: Vec<Address>,
+) -> Option<Messages> {
+    if current
+        .iter()
+        .map(|(client, _)| &client.address)
+        .eq(selected.iter())
+    {
+        return None;
+    }
+    let clients = Clients::get().ok()?;
+    let mut extras = client_extras().unwrap_or_default();
+    let windows = selected
+        .iter()
+        .filter_map(|address| clients.iter().find(|client| client.address == *address))
+        .map(|client| {
+            let client_extras = extras
+                .remove(&client.address.to_string())
+                .unwrap_or_default();
+            (client.clone(), client_extras)
+        })
+        .collect();
+    Some(Messages::SelectionUpdate(windows))
+}
+
+fn handle_event(model: &Model) -> color_eyre::Result<Option<Messages>> {
     if event::poll(Duration::from_millis(16))? {
         if let Event::Key(key) = event::read()? {
             if key.kind == event::KeyEventKind::Press {
-                return Ok(handle_key(key));
+                return Ok(handle_key(model, key));
             }
         }
     }
     Ok(None)
 }
 
-fn handle_key(key: event::KeyEvent) -> Option<Messages> {
+fn handle_key(model: &Model, key: event::KeyEvent) -> Option<Messages> {
+    if model.page == PageState::RuleOptions && model.rule_options.editing {
+        return match key.code {
+            KeyCode::Enter => Some(Messages::FinishEditing),
+            KeyCode::Esc => Some(Messages::ClearValue),
+            KeyCode::Backspace => Some(Messages::Backspace),
+            KeyCode::Char(character) => Some(Messages::Input(character)),
+            _ => None,
+        };
+    }
     match key.code {
-        KeyCode::Char('q') => Some(Messages::RunningState(RunningState::Done)),
+        KeyCode::Char('q') => Some(Messages::RunningState(RunningState::Unpicked(
+            Unpicked::Cancelled,
+        ))),
+        KeyCode::Up | KeyCode::Char('k') => Some(Messages::MoveCursor(-1)),
+        KeyCode::Down | KeyCode::Char('j') => Some(Messages::MoveCursor(1)),
+        KeyCode::Char(' ') => Some(Messages::Toggle),
+        KeyCode::Enter => Some(Messages::Confirm),
         _ => None,
     }
 }
 
-mod tui {
+pub(super) mod tui {
     use ratatui::{
         Terminal,
         backend::{Backend, CrosstermBackend},
@@ -199,17 +594,18 @@ mod tui {
             },
         },
     };
-    use std::{io::stdout, panic};
+    use std::{io::stderr, panic};
 
+    /// Draws on stderr so `generate --stdout` can be piped without the TUI ending up in the pipe.
     pub fn init_terminal() -> color_eyre::Result<Terminal<impl Backend>> {
         enable_raw_mode()?;
-        stdout().execute(EnterAlternateScreen)?;
-        let terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+        stderr().execute(EnterAlternateScreen)?;
+        let terminal = Terminal::new(CrosstermBackend::new(stderr()))?;
         Ok(terminal)
     }
 
     pub fn restore_terminal() -> color_eyre::Result<()> {
-        stdout().execute(LeaveAlternateScreen)?;
+        stderr().execute(LeaveAlternateScreen)?;
         disable_raw_mode()?;
         Ok(())
     }
@@ -217,7 +613,7 @@ mod tui {
     pub fn install_panic_hook() {
         let original_hook = panic::take_hook();
         panic::set_hook(Box::new(move |panic_info| {
-            stdout().execute(LeaveAlternateScreen).unwrap();
+            stderr().execute(LeaveAlternateScreen).unwrap();
             disable_raw_mode().unwrap();
             original_hook(panic_info);
         }));
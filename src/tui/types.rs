@@ -0,0 +1,406 @@
+use hyprland::data::Client;
+
+use crate::hyprland_config::{
+    ClientExtras, LayerAction, SelectWindowBy, WindowAction, WindowOptions, WindowPlacement,
+    literal_matcher,
+};
+
+const TOGGLE_KEYWORDS: [&str; 15] = [
+    "fullscreen",
+    "maximize",
+    "pseudo",
+    "center",
+    "pin",
+    "noblur",
+    "noshadow",
+    "noborder",
+    "noanim",
+    "nodim",
+    "nofocus",
+    "noinitialfocus",
+    "dimaround",
+    "keepaspectratio",
+    "stayfocused",
+];
+
+const VALUE_KEYWORDS: [&str; 12] = [
+    "size",
+    "minsize",
+    "maxsize",
+    "move",
+    "workspace",
+    "monitor",
+    "opacity",
+    "rounding",
+    "bordersize",
+    "idleinhibit",
+    "suppressevent",
+    "tag",
+];
+
+const LAYER_TOGGLE_KEYWORDS: [&str; 4] = ["blur", "ignorezero", "noanim", "dimaround"];
+
+const LAYER_VALUE_KEYWORDS: [&str; 4] = ["ignorealpha", "animation", "xray", "order"];
+
+/// What the rule options page builds a rule for, which decides the rows it offers.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RuleTarget {
+    Window,
+    Layer,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RuleOptionKind {
+    /// Cycles between no placement, float and tile
+    Placement,
+    /// An action keyword that is either on or off
+    Toggle,
+    /// An action keyword that needs a value typed in, e.g. `size 800 600`
+    Value,
+}
+
+#[derive(Debug)]
+pub struct RuleOption {
+    pub keyword: &'static str,
+    pub kind: RuleOptionKind,
+    pub enabled: bool,
+    pub value: String,
+}
+
+/// State of the rule options page, one row per action with the placement row first for
+/// window rules.
+#[derive(Debug)]
+pub struct RuleOptionsState {
+    pub target: RuleTarget,
+    pub options: Vec<RuleOption>,
+    pub placement: Option<WindowPlacement>,
+    pub cursor: usize,
+    pub editing: bool,
+    pub error: Option<String>,
+    /// Tags already on open windows, the hovered window's first, shown on the tag row so they
+    /// get reused
+    pub known_tags: Vec<String>,
+}
+
+impl RuleOptionsState {
+    pub fn new() -> Self {
+        let placement = RuleOption {
+            keyword: "placement",
+            kind: RuleOptionKind::Placement,
+            enabled: false,
+            value: String::new(),
+        };
+        Self::with_keywords(
+            RuleTarget::Window,
+            Some(placement),
+            &TOGGLE_KEYWORDS,
+            &VALUE_KEYWORDS,
+        )
+    }
+
+    /// The layer rule effects, which have no placement.
+    pub fn for_layers() -> Self {
+        Self::with_keywords(
+            RuleTarget::Layer,
+            None,
+            &LAYER_TOGGLE_KEYWORDS,
+            &LAYER_VALUE_KEYWORDS,
+        )
+    }
+
+    fn with_keywords(
+        target: RuleTarget,
+        placement: Option<RuleOption>,
+        toggle_keywords: &[&'static str],
+        value_keywords: &[&'static str],
+    ) -> Self {
+        let toggles = toggle_keywords.iter().map(|keyword| RuleOption {
+            keyword,
+            kind: RuleOptionKind::Toggle,
+            enabled: false,
+            value: String::new(),
+        });
+        let values = value_keywords.iter().map(|keyword| RuleOption {
+            keyword,
+            kind: RuleOptionKind::Value,
+            enabled: false,
+            value: String::new(),
+        });
+        Self {
+            target,
+            options: placement.into_iter().chain(toggles).chain(values).collect(),
+            placement: None,
+            cursor: 0,
+            editing: false,
+            error: None,
+            known_tags: vec![],
+        }
+    }
+
+    /// Start from the actions of an existing rule, written the windowrulev2 way.
+    pub fn with_rules(rules: &[String]) -> Result<Self, String> {
+        let mut state = Self::new();
+        for rule in rules {
+            let (keyword, value) = match rule.split_once(' ') {
+                Some((keyword, value)) => (keyword, value.trim()),
+                None => (rule.as_str(), ""),
+            };
+            match keyword {
+                "float" => state.placement = Some(WindowPlacement::Float),
+                "tile" => state.placement = Some(WindowPlacement::Tile),
+                keyword => {
+                    let option = state
+                        .options
+                        .iter_mut()
+                        .find(|option| option.keyword == keyword)
+                        .ok_or_else(|| format!("\"{rule}\" can't be edited"))?;
+                    option.enabled = true;
+                    option.value = value.to_string();
+                }
+            }
+        }
+        state.options[0].enabled = state.placement.is_some();
+        Ok(state)
+    }
+
+    pub fn move_cursor(&mut self, offset: isize) {
+        self.cursor = self
+            .cursor
+            .saturating_add_signed(offset)
+            .min(self.options.len() - 1);
+    }
+
+    /// Space on the current row: cycle the placement, flip a toggle, or start typing a value.
+    pub fn toggle(&mut self) {
+        let option = &mut self.options[self.cursor];
+        match option.kind {
+            RuleOptionKind::Placement => {
+                self.placement = match self.placement {
+                    None => Some(WindowPlacement::Float),
+                    Some(WindowPlacement::Float) => Some(WindowPlacement::Tile),
+                    Some(WindowPlacement::Tile) => None,
+                };
+                option.enabled = self.placement.is_some();
+            }
+            RuleOptionKind::Toggle => option.enabled = !option.enabled,
+            RuleOptionKind::Value => self.editing = true,
+        }
+    }
+
+    pub fn input(&mut self, character: char) {
+        self.options[self.cursor].value.push(character);
+    }
+
+    pub fn backspace(&mut self) {
+        self.options[self.cursor].value.pop();
+    }
+
+    /// Leave the value editor, an empty value turns the action back off.
+    pub fn finish_editing(&mut self) {
+        self.editing = false;
+        let option = &mut self.options[self.cursor];
+        option.enabled = !option.value.trim().is_empty();
+        self.error = match option.enabled {
+            true => (!is_valid_rule(self.target, &format!("{} {}", option.keyword, option.value)))
+                .then(|| {
+                    format!(
+                        "\"{}\" is not a valid {} value",
+                        option.value, option.keyword
+                    )
+                }),
+            false => None,
+        };
+    }
+
+    /// The enabled action rows as rule text, without the placement.
+    fn action_rules(&self) -> impl Iterator<Item = String> {
+        self.options
+            .iter()
+            .filter(|option| option.enabled)
+            .filter_map(|option| match option.kind {
+                RuleOptionKind::Placement => None,
+                RuleOptionKind::Toggle => Some(option.keyword.to_string()),
+                RuleOptionKind::Value => Some(format!("{} {}", option.keyword, option.value)),
+            })
+    }
+
+    pub fn window_options(&self) -> Result<WindowOptions, String> {
+        let actions = self
+            .action_rules()
+            .map(|rule| {
+                WindowAction::from_rule(&rule).ok_or_else(|| format!("\"{rule}\" is not valid"))
+            })
+            .collect::<Result<Vec<WindowAction>, String>>()?;
+
+        let window_options = WindowOptions {
+            window_placement: self.placement.clone(),
+            actions,
+        };
+        match window_options.rules().is_empty() {
+            true => Err("Pick at least one action".to_string()),
+            false => Ok(window_options),
+        }
+    }
+
+    pub fn layer_actions(&self) -> Result<Vec<LayerAction>, String> {
+        let actions = self
+            .action_rules()
+            .map(|rule| {
+                LayerAction::from_rule(&rule).ok_or_else(|| format!("\"{rule}\" is not valid"))
+            })
+            .collect::<Result<Vec<LayerAction>, String>>()?;
+        match actions.is_empty() {
+            true => Err("Pick at least one action".to_string()),
+            false => Ok(actions),
+        }
+    }
+}
+
+fn is_valid_rule(target: RuleTarget, rule: &str) -> bool {
+    match target {
+        RuleTarget::Window => WindowAction::from_rule(rule).is_some(),
+        RuleTarget::Layer => LayerAction::from_rule(rule).is_some(),
+    }
+}
+
+/// State of the page that picks which client properties the rule matches on.
+#[derive(Debug)]
+pub struct SelectByState {
+    pub options: Vec<(SelectWindowBy, bool)>,
+    pub cursor: usize,
+    pub error: Option<String>,
+}
+
+impl SelectByState {
+    /// Start with the `selected` properties ticked.
+    pub fn new(selected: &[SelectWindowBy]) -> Self {
+        Self {
+            options: SelectWindowBy::all()
+                .into_iter()
+                .map(|select_by| {
+                    let selected = selected.contains(&select_by);
+                    (select_by, selected)
+                })
+                .collect(),
+            cursor: 0,
+            error: None,
+        }
+    }
+
+    pub fn move_cursor(&mut self, offset: isize) {
+        self.cursor = self
+            .cursor
+            .saturating_add_signed(offset)
+            .min(self.options.len() - 1);
+    }
+
+    pub fn toggle(&mut self) {
+        let (_, selected) = &mut self.options[self.cursor];
+        *selected = !*selected;
+    }
+
+    pub fn select_by_list(&self) -> Result<Vec<SelectWindowBy>, String> {
+        let select_by_list: Vec<SelectWindowBy> = self
+            .options
+            .iter()
+            .filter(|(_, selected)| *selected)
+            .map(|(select_by, _)| select_by.clone())
+            .collect();
+        match select_by_list.is_empty() {
+            true => Err("Pick at least one property to match on".to_string()),
+            false => Ok(select_by_list),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SelectorOption {
+    pub select_by: SelectWindowBy,
+    /// Empty when the rule doesn't match on this property
+    pub pattern: String,
+}
+
+/// State of the page that edits the patterns of an existing rule. Unlike [`SelectByState`]
+/// there may be no window to take values from, so patterns are typed in, starting from the
+/// matching window's value when one is open.
+#[derive(Debug)]
+pub struct SelectorState {
+    pub options: Vec<SelectorOption>,
+    pub cursor: usize,
+    pub editing: bool,
+    pub error: Option<String>,
+    client: Option<(Client, ClientExtras)>,
+}
+
+impl SelectorState {
+    pub fn new(
+        matchers: &[(SelectWindowBy, String)],
+        client: Option<(Client, ClientExtras)>,
+    ) -> Self {
+        let options = SelectWindowBy::all()
+            .into_iter()
+            .map(|select_by| SelectorOption {
+                pattern: matchers
+                    .iter()
+                    .find(|(matcher, _)| *matcher == select_by)
+                    .map(|(_, pattern)| pattern.clone())
+                    .unwrap_or_default(),
+                select_by,
+            })
+            .collect();
+        Self {
+            options,
+            cursor: 0,
+            editing: false,
+            error: None,
+            client,
+        }
+    }
+
+    pub fn move_cursor(&mut self, offset: isize) {
+        self.cursor = self
+            .cursor
+            .saturating_add_signed(offset)
+            .min(self.options.len() - 1);
+    }
+
+    pub fn start_editing(&mut self) {
+        let option = &mut self.options[self.cursor];
+        if let (true, Some((client, extras))) = (option.pattern.is_empty(), &self.client)
+            && let Some(value) = option.select_by.client_value(client, extras)
+        {
+            option.pattern = match option.select_by.is_regex() {
+                true => literal_matcher(&value),
+                false => value,
+            };
+        }
+        self.editing = true;
+    }
+
+    pub fn input(&mut self, character: char) {
+        self.options[self.cursor].pattern.push(character);
+    }
+
+    pub fn backspace(&mut self) {
+        self.options[self.cursor].pattern.pop();
+    }
+
+    pub fn finish_editing(&mut self) {
+        self.editing = false;
+        let pattern = &mut self.options[self.cursor].pattern;
+        *pattern = pattern.trim().to_string();
+    }
+
+    pub fn matchers(&self) -> Result<Vec<(SelectWindowBy, String)>, String> {
+        let matchers: Vec<(SelectWindowBy, String)> = self
+            .options
+            .iter()
+            .filter(|option| !option.pattern.is_empty())
+            .map(|option| (option.select_by.clone(), option.pattern.clone()))
+            .collect();
+        match matchers.is_empty() {
+            true => Err("Match on at least one property".to_string()),
+            false => Ok(matchers),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use crate::hyprland_config::LayerSurface;
+use ratatui::prelude::*;
+
+use ratatui::Frame;
+
+/// The layer surfaces under a heading for each monitor and level, `layers` must already be
+/// sorted so each group is contiguous.
+pub fn layer_list(layers: &[LayerSurface], cursor: usize, frame: &mut Frame) {
+    let layout = Layout::new(
+        Direction::Vertical,
+        [
+            Constraint::Length(2),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ],
+    )
+    .flex(layout::Flex::Start)
+    .spacing(0)
+    .split(frame.area());
+
+    frame.render_widget(Line::from("Select a layer namespace".bold()), layout[0]);
+
+    let mut rows: Vec<Line> = Vec::new();
+    let mut cursor_row = 0;
+    let mut group: Option<(&str, &str)> = None;
+    for (index, layer) in layers.iter().enumerate() {
+        if group != Some((&layer.monitor, layer.level)) {
+            group = Some((&layer.monitor, layer.level));
+            rows.push(Line::from(
+                format!("{} / {}", layer.monitor, layer.level).underlined(),
+            ));
+        }
+        let line = Line::from(vec![
+            format!("  {:<32}", layer.namespace).bold(),
+            format!("{}x{}", layer.width, layer.height).dim(),
+        ]);
+        rows.push(match index == cursor {
+            true => {
+                cursor_row = rows.len();
+                line.reversed()
+            }
+            false => line,
+        });
+    }
+
+    let visible_rows = layout[1].height as usize;
+    let first_row = cursor_row.saturating_sub(visible_rows.saturating_sub(1));
+    let rows: Vec<Line> = rows
+        .into_iter()
+        .skip(first_row)
+        .take(visible_rows)
+        .collect();
+    frame.render_widget(Text::from(rows), layout[1]);
+
+    frame.render_widget(
+        Line::from("up/down: move, enter: pick namespace, q: quit".dim()),
+        layout[2],
+    );
+}
@@ -0,0 +1,44 @@
+use hyprland::data::Client;
+use ratatui::prelude::*;
+
+/// The open windows a rule for `selected_client` matches, itself highlighted, or why it can't
+/// match any.
+pub fn match_preview<'c>(
+    selected_client: &Client,
+    matching_clients: &'c Result<Vec<Client>, String>,
+) -> Text<'c> {
+    let matching_clients = match matching_clients {
+        Ok(matching_clients) => matching_clients,
+        Err(error) => {
+            return Text::from(Line::from(
+                format!("The rule matches no window: {error}").bold().red(),
+            ));
+        }
+    };
+    let others = matching_clients
+        .iter()
+        .filter(|client| client.address != selected_client.address)
+        .count();
+    let heading = match (matching_clients.is_empty(), others) {
+        (true, _) => Line::from("The rule matches no open window".bold().red()),
+        (false, 0) => Line::from("The rule matches only this window".bold()),
+        (false, others) => Line::from(
+            format!("The rule also matches {others} other window(s)")
+                .bold()
+                .yellow(),
+        ),
+    };
+
+    let rows = matching_clients.iter().map(|client| {
+        let line = Line::from(vec![
+            format!("{:<8}", client.workspace.name).dim(),
+            format!("{} ", client.class).bold(),
+            client.title.as_str().into(),
+        ]);
+        match client.address == selected_client.address {
+            true => line.reversed(),
+            false => line,
+        }
+    });
+    Text::from_iter(std::iter::once(heading).chain(rows))
+}
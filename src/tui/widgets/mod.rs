@@ -1,2 +1,8 @@
+pub(super) mod layer_list;
+pub(super) mod match_preview;
+pub(super) mod rule_options;
+pub(super) mod select_by;
+pub(super) mod selector;
 pub(super) mod window_info_row;
 pub(super) mod window_select;
+pub(super) mod workspace_list;
@@ -0,0 +1,75 @@
+use super::window_info_row::window_info_row;
+use crate::hyprland_config::WindowPlacement;
+use crate::tui::types::{RuleOptionKind, RuleOptionsState};
+use ratatui::prelude::*;
+
+use ratatui::Frame;
+
+pub fn rule_options(title: &str, state: &RuleOptionsState, frame: &mut Frame) {
+    let layout = Layout::new(
+        Direction::Vertical,
+        [
+            Constraint::Length(2),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ],
+    )
+    .flex(layout::Flex::Start)
+    .spacing(0)
+    .split(frame.area());
+
+    frame.render_widget(window_info_row("Rule actions for: ", title), layout[0]);
+
+    // Keep the cursor on screen when the list is taller than the terminal
+    let visible_rows = layout[1].height as usize;
+    let first_row = state.cursor.saturating_sub(visible_rows.saturating_sub(1));
+    let rows: Vec<Line> = state
+        .options
+        .iter()
+        .enumerate()
+        .skip(first_row)
+        .take(visible_rows)
+        .map(|(index, option)| {
+            let checkbox = if option.enabled { "[x] " } else { "[ ] " };
+            let value = match option.kind {
+                RuleOptionKind::Placement => match state.placement {
+                    Some(WindowPlacement::Float) => "float".to_string(),
+                    Some(WindowPlacement::Tile) => "tile".to_string(),
+                    None => "unchanged".to_string(),
+                },
+                RuleOptionKind::Toggle => String::new(),
+                RuleOptionKind::Value if index == state.cursor && state.editing => {
+                    format!("{}_", option.value)
+                }
+                RuleOptionKind::Value => option.value.clone(),
+            };
+            let line = Line::from(vec![
+                checkbox.into(),
+                format!("{:<16}", option.keyword).bold(),
+                value.dim(),
+            ]);
+            match index == state.cursor {
+                true => line.reversed(),
+                false => line,
+            }
+        })
+        .collect();
+    frame.render_widget(Text::from(rows), layout[1]);
+
+    let on_tag_row = state.options[state.cursor].keyword == "tag";
+    match &state.error {
+        Some(error) => frame.render_widget(Line::from(error.as_str().red()), layout[2]),
+        None if on_tag_row && !state.known_tags.is_empty() => frame.render_widget(
+            Line::from(format!("tags in use: {}", state.known_tags.join(", ")).dim()),
+            layout[2],
+        ),
+        None => {}
+    }
+
+    let help = match state.editing {
+        true => "type a value, enter: done, esc: clear",
+        false => "up/down: move, space: toggle/edit, enter: continue, q: quit",
+    };
+    frame.render_widget(Line::from(help.dim()), layout[3]);
+}
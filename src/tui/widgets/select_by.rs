@@ -0,0 +1,68 @@
+use super::match_preview::match_preview;
+use super::window_info_row::window_info_row;
+use crate::hyprland_config::ClientExtras;
+use crate::tui::types::SelectByState;
+use hyprland::data::Client;
+use ratatui::prelude::*;
+
+use ratatui::Frame;
+
+/// The properties `client` could be matched on, with the open windows the ticked ones match
+/// below them.
+pub fn select_by(
+    client: &Client,
+    extras: &ClientExtras,
+    state: &SelectByState,
+    matching_clients: &Result<Vec<Client>, String>,
+    frame: &mut Frame,
+) {
+    let layout = Layout::new(
+        Direction::Vertical,
+        [
+            Constraint::Length(2),
+            Constraint::Length(state.options.len() as u16),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ],
+    )
+    .flex(layout::Flex::Start)
+    .spacing(0)
+    .split(frame.area());
+
+    frame.render_widget(
+        window_info_row("Match the rule on: ", client.title.as_str()),
+        layout[0],
+    );
+
+    let rows: Vec<Line> = state
+        .options
+        .iter()
+        .enumerate()
+        .map(|(index, (select_by, selected))| {
+            let checkbox = if *selected { "[x] " } else { "[ ] " };
+            let value = select_by
+                .client_value(client, extras)
+                .unwrap_or_else(|| "(none)".to_string());
+            let line = Line::from(vec![
+                checkbox.into(),
+                format!("{}: ", select_by.label()).bold(),
+                value.dim(),
+            ]);
+            match index == state.cursor {
+                true => line.reversed(),
+                false => line,
+            }
+        })
+        .collect();
+    frame.render_widget(Text::from(rows), layout[1]);
+
+    if let Some(error) = &state.error {
+        frame.render_widget(Line::from(error.as_str().red()), layout[2]);
+    }
+    frame.render_widget(match_preview(client, matching_clients), layout[3]);
+    frame.render_widget(
+        Line::from("up/down: move, space: toggle, enter: write rule, q: quit".dim()),
+        layout[4],
+    );
+}
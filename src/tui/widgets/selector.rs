@@ -0,0 +1,56 @@
+use super::window_info_row::window_info_row;
+use crate::tui::types::SelectorState;
+use ratatui::prelude::*;
+
+use ratatui::Frame;
+
+pub fn selector(title: &str, state: &SelectorState, frame: &mut Frame) {
+    let layout = Layout::new(
+        Direction::Vertical,
+        [
+            Constraint::Length(2),
+            Constraint::Length(state.options.len() as u16),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ],
+    )
+    .flex(layout::Flex::Start)
+    .spacing(0)
+    .split(frame.area());
+
+    frame.render_widget(window_info_row("Match the rule on: ", title), layout[0]);
+
+    let rows: Vec<Line> = state
+        .options
+        .iter()
+        .enumerate()
+        .map(|(index, option)| {
+            let checkbox = if option.pattern.is_empty() {
+                "[ ] "
+            } else {
+                "[x] "
+            };
+            let label = format!("{}: ", option.select_by.label());
+            let pattern = match index == state.cursor && state.editing {
+                true => format!("{}_", option.pattern),
+                false => option.pattern.clone(),
+            };
+            let line = Line::from(vec![checkbox.into(), label.bold(), pattern.dim()]);
+            match index == state.cursor {
+                true => line.reversed(),
+                false => line,
+            }
+        })
+        .collect();
+    frame.render_widget(Text::from(rows), layout[1]);
+
+    if let Some(error) = &state.error {
+        frame.render_widget(Line::from(error.as_str().red()), layout[2]);
+    }
+
+    let help = match state.editing {
+        true => "type a pattern, enter: done, esc: stop matching on it",
+        false => "up/down: move, space: edit, enter: save rule, q: quit",
+    };
+    frame.render_widget(Line::from(help.dim()), layout[3]);
+}
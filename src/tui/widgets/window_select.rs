@@ -1,13 +1,35 @@
+use super::match_preview::match_preview;
 use super::window_info_row::window_info_row;
+use crate::hyprland_config::ClientExtras;
 use hyprland::data::Client;
 use ratatui::prelude::*;
 
 use ratatui::Frame;
 
-pub fn select_window(hovered_client: &Option<Client>, frame: &mut Frame) {
+/// The hovered client's properties, with the open windows a rule for it would match beside
+/// them. `matching_clients` includes the hovered client itself, or says why the rule can't
+/// match anything. `selected` holds the windows picked so far in multi-select mode, and is
+/// `None` outside of it. `click_hint`, what each button does, goes in the footer.
+pub fn select_window(
+    hovered_client: &Option<Client>,
+    hovered_extras: &ClientExtras,
+    matching_clients: &Result<Vec<Client>, String>,
+    selected: Option<&[(Client, ClientExtras)]>,
+    click_hint: Option<&str>,
+    frame: &mut Frame,
+) {
+    let selected_height = match selected {
+        Some(selected) => selected.len() as u16 + 2,
+        None => 0,
+    };
     let layout = Layout::new(
         Direction::Vertical,
-        [Constraint::Length(4), Constraint::Fill(1)],
+        [
+            Constraint::Length(4),
+            Constraint::Length(selected_height),
+            Constraint::Fill(1),
+            Constraint::Length(click_hint.map_or(0, |_| 1)),
+        ],
     )
     .flex(layout::Flex::Start)
     .spacing(0)
@@ -15,7 +37,10 @@ pub fn select_window(hovered_client: &Option<Client>, frame: &mut Frame) {
 
     frame.render_widget(
         window_info_row(
-            "Select a window: ",
+            match selected {
+                Some(_) => "Select windows: ",
+                None => "Select a window: ",
+            },
             match &hovered_client {
                 Some(selected_client) => selected_client.title.as_str(),
                 None => "",
@@ -24,19 +49,35 @@ pub fn select_window(hovered_client: &Option<Client>, frame: &mut Frame) {
         layout[0],
     );
 
+    if let Some(selected) = selected {
+        frame.render_widget(selection_list(selected), layout[1]);
+    }
+
+    if let Some(click_hint) = click_hint {
+        frame.render_widget(Line::from(click_hint.dim()), layout[3]);
+    }
+
     if let Some(selected_client) = &hovered_client {
+        let panels = Layout::new(
+            Direction::Horizontal,
+            [Constraint::Fill(1), Constraint::Fill(1)],
+        )
+        .spacing(2)
+        .split(layout[2]);
+
         let details = Layout::new(
             Direction::Vertical,
             [
-                Constraint::Fill(1),
-                Constraint::Fill(1),
-                Constraint::Fill(1),
-                Constraint::Fill(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
             ],
         )
         .flex(layout::Flex::Start)
         .spacing(0)
-        .split(layout[1]);
+        .split(panels[0]);
 
         frame.render_widget(
             window_info_row("Title: ", selected_client.title.as_str()),
@@ -54,5 +95,23 @@ pub fn select_window(hovered_client: &Option<Client>, frame: &mut Frame) {
             window_info_row("Initial Class: ", selected_client.initial_class.as_str()),
             details[3],
         );
+        let tags = hovered_extras.tag_names().collect::<Vec<&str>>().join(", ");
+        frame.render_widget(window_info_row("Tags: ", &tags), details[4]);
+
+        frame.render_widget(match_preview(selected_client, matching_clients), panels[1]);
     }
 }
+
+fn selection_list(selected: &[(Client, ClientExtras)]) -> Text<'_> {
+    let heading = Line::from(vec![
+        format!("{} selected ", selected.len()).bold(),
+        "(click or space toggles a window, enter or right click when done)".dim(),
+    ]);
+    let rows = selected.iter().map(|(client, _)| {
+        Line::from(vec![
+            format!("{} ", client.class).bold(),
+            client.title.as_str().into(),
+        ])
+    });
+    Text::from_iter(std::iter::once(heading).chain(rows))
+}
@@ -0,0 +1,46 @@
+use hyprland::data::Workspace;
+use ratatui::prelude::*;
+
+use ratatui::Frame;
+
+pub fn workspace_list(workspaces: &[Workspace], cursor: usize, frame: &mut Frame) {
+    let layout = Layout::new(
+        Direction::Vertical,
+        [
+            Constraint::Length(2),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ],
+    )
+    .flex(layout::Flex::Start)
+    .spacing(0)
+    .split(frame.area());
+
+    frame.render_widget(Line::from("Select a workspace".bold()), layout[0]);
+
+    let visible_rows = layout[1].height as usize;
+    let first_row = cursor.saturating_sub(visible_rows.saturating_sub(1));
+    let rows: Vec<Line> = workspaces
+        .iter()
+        .enumerate()
+        .skip(first_row)
+        .take(visible_rows)
+        .map(|(index, workspace)| {
+            let line = Line::from(vec![
+                format!("{:<16}", workspace.name).bold(),
+                format!("on {:<12}", workspace.monitor).into(),
+                format!("{} windows", workspace.windows).dim(),
+            ]);
+            match index == cursor {
+                true => line.reversed(),
+                false => line,
+            }
+        })
+        .collect();
+    frame.render_widget(Text::from(rows), layout[1]);
+
+    frame.render_widget(
+        Line::from("up/down: move, enter: write rule, q: quit".dim()),
+        layout[2],
+    );
+}
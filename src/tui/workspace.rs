@@ -0,0 +1,58 @@
+use super::root::tui;
+use super::widgets::workspace_list::workspace_list;
+
+use color_eyre::Result;
+use hyprland::data::Workspace;
+use ratatui::crossterm::event;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use std::time::Duration;
+
+enum Messages {
+    Quit,
+    MoveCursor(isize),
+    Confirm,
+}
+
+/// Pick one of the current workspaces from a list.
+pub fn app(workspaces: Vec<Workspace>) -> Result<Option<Workspace>> {
+    if workspaces.is_empty() {
+        return Ok(None);
+    }
+
+    tui::install_panic_hook();
+    let mut terminal = tui::init_terminal()?;
+    let mut cursor = 0;
+    let selected = loop {
+        terminal.draw(|frame| workspace_list(&workspaces, cursor, frame))?;
+        match handle_event()? {
+            Some(Messages::Quit) => break None,
+            Some(Messages::MoveCursor(offset)) => {
+                cursor = cursor
+                    .saturating_add_signed(offset)
+                    .min(workspaces.len() - 1);
+            }
+            Some(Messages::Confirm) => break Some(workspaces[cursor].clone()),
+            None => {}
+        }
+    };
+    tui::restore_terminal()?;
+
+    Ok(selected)
+}
+
+fn handle_event() -> Result<Option<Messages>> {
+    if event::poll(Duration::from_millis(16))?
+        && let Event::Key(key) = event::read()?
+        && key.kind == event::KeyEventKind::Press
+    {
+        return Ok(match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => Some(Messages::Quit),
+            KeyCode::Up | KeyCode::Char('k') => Some(Messages::MoveCursor(-1)),
+            KeyCode::Down | KeyCode::Char('j') => Some(Messages::MoveCursor(1)),
+            KeyCode::Enter => Some(Messages::Confirm),
+            _ => None,
+        });
+    }
+    Ok(None)
+}
@@ -69,6 +69,9 @@ impl HasOutput {
             .set_size(self.size.0 as u32, self.size.1 as u32);
         self.wlr_surface
             .set_anchor(zwlr_layer_surface_v1::Anchor::Top);
+        // Take the keyboard so the overlay can be used without a mouse
+        self.wlr_surface
+            .set_keyboard_interactivity(zwlr_layer_surface_v1::KeyboardInteractivity::Exclusive);
 
         self.base_surface.commit();
     }
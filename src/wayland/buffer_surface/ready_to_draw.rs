@@ -1,4 +1,6 @@
+use crate::config::OverlayColors;
 use crate::wayland::ClientRegion;
+use hyprland::shared::Address;
 
 use super::super::protocols::State;
 use super::has_output::HasOutput;
@@ -60,8 +62,23 @@ impl ReadyToDraw {
         self.wlr_surface.ack_configure(serial);
     }
 
-    pub fn actually_draw_buffer_surface(&mut self, shm: &wl_shm::WlShm, qh: &QueueHandle<State>) {
-        self.buffer = create_surface_buffer(&shm, qh, self.monitor_clients.clone(), self.size);
+    pub fn actually_draw_buffer_surface(
+        &mut self,
+        shm: &wl_shm::WlShm,
+        qh: &QueueHandle<State>,
+        colors: &OverlayColors,
+        selected: &[Address],
+        focused: Option<&Address>,
+    ) {
+        self.buffer = create_surface_buffer(
+            &shm,
+            qh,
+            self.monitor_clients.clone(),
+            self.size,
+            colors,
+            selected,
+            focused,
+        );
         self.base_surface
             .attach(Some(&self.buffer), self.size.0 as i32, self.size.1 as i32);
         self.base_surface.commit();
@@ -73,12 +90,22 @@ fn create_surface_buffer(
     qh: &QueueHandle<State>,
     monitor_clients: Vec<ClientRegion>,
     size: (u16, u16),
+    colors: &OverlayColors,
+    selected: &[Address],
+    focused: Option<&Address>,
 ) -> wl_buffer::WlBuffer {
     let (init_w, init_h) = size;
 
     let mut file = tempfile::tempfile().unwrap();
 
-    draw(&mut file, (init_w as i16, init_h as i16), monitor_clients);
+    draw(
+        &mut file,
+        (init_w as i16, init_h as i16),
+        monitor_clients,
+        colors,
+        selected,
+        focused,
+    );
     let pool = shm.create_pool(file.as_fd(), init_w as i32 * init_h as i32 * 4, qh, ());
     let buffer = pool.create_buffer(
         0,
@@ -91,9 +118,6 @@ fn create_surface_buffer(
     );
     return buffer.clone();
 }
-const BG_COLOR: [u8; 4] = [0x00 as u8, 0x00 as u8, 0x00 as u8, 0x00 as u8];
-const FG_COLOR: [u8; 4] = [0x40 as u8, 0x40 as u8, 0x40 as u8, 0x2F as u8];
-
 fn is_inside_region((x_cord, y_cord): (i16, i16), client: &ClientRegion) -> bool {
     let x = client.at.0 < x_cord && x_cord < (client.at.0 + client.size.0);
     let y = client.at.1 < y_cord && y_cord < (client.at.1 + client.size.1);
@@ -109,7 +133,18 @@ fn is_inside_region((x_cord, y_cord): (i16, i16), client: &ClientRegion) -> bool
     return false;
 }
  */
-fn draw(tmp: &mut File, (buf_x, buf_y): (i16, i16), monitor_clients: Vec<ClientRegion>) {
+fn draw(
+    tmp: &mut File,
+    (buf_x, buf_y): (i16, i16),
+    monitor_clients: Vec<ClientRegion>,
+    colors: &OverlayColors,
+    selected: &[Address],
+    focused: Option<&Address>,
+) {
+    let foreground = colors.foreground.argb8888_bytes();
+    let selected_color = colors.selected.argb8888_bytes();
+    let focused_color = colors.focused.argb8888_bytes();
+    let background = colors.background.argb8888_bytes();
     /*     let start = SystemTime::now()
     .duration_since(UNIX_EPOCH)
     .expect("time should go forward"); */
@@ -118,10 +153,16 @@ fn draw(tmp: &mut File, (buf_x, buf_y): (i16, i16), monitor_clients: Vec<ClientR
         for x in 0..buf_x {
             match monitor_clients
                 .iter()
-                .any(|client| is_inside_region((x, y), client))
+                .find(|client| is_inside_region((x, y), client))
             {
-                true => buf.write_all(&FG_COLOR).unwrap(),
-                false => buf.write_all(&BG_COLOR).unwrap(),
+                Some(client) if focused == Some(&client.client_id) => {
+                    buf.write_all(&focused_color).unwrap()
+                }
+                Some(client) if selected.contains(&client.client_id) => {
+                    buf.write_all(&selected_color).unwrap()
+                }
+                Some(_) => buf.write_all(&foreground).unwrap(),
+                None => buf.write_all(&background).unwrap(),
             };
         }
     }
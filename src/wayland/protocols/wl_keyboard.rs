@@ -1,8 +1,30 @@
 use wayland_client::{
+    WEnum,
     protocol::wl_keyboard,
     {Connection, Dispatch, QueueHandle},
 };
 
+use super::Navigation;
+
+/// Linux input event codes, from `linux/input-event-codes.h`. The overlay doesn't load the
+/// keymap, so keys are matched by their position on the keyboard.
+const KEY_ESC: u32 = 1;
+const KEY_TAB: u32 = 15;
+const KEY_ENTER: u32 = 28;
+const KEY_H: u32 = 35;
+const KEY_J: u32 = 36;
+const KEY_K: u32 = 37;
+const KEY_L: u32 = 38;
+const KEY_SPACE: u32 = 57;
+const KEY_KPENTER: u32 = 96;
+const KEY_UP: u32 = 103;
+const KEY_LEFT: u32 = 105;
+const KEY_RIGHT: u32 = 106;
+const KEY_DOWN: u32 = 108;
+
+/// Shift in the modifier mask of the standard xkb keymaps.
+const SHIFT_MASK: u32 = 1;
+
 impl Dispatch<wl_keyboard::WlKeyboard, ()> for super::State {
     fn event(
         state: &mut Self,
@@ -12,11 +34,33 @@ impl Dispatch<wl_keyboard::WlKeyboard, ()> for super::State {
         _: &Connection,
         _: &QueueHandle<Self>,
     ) {
-        if let wl_keyboard::Event::Key { key, .. } = event {
-            if key == 1 {
-                // ESC key
-                state.running = false;
+        match event {
+            wl_keyboard::Event::Modifiers { mods_depressed, .. } => {
+                state.shift_held = mods_depressed & SHIFT_MASK != 0;
             }
+            wl_keyboard::Event::Key {
+                key,
+                state: WEnum::Value(wl_keyboard::KeyState::Pressed),
+                ..
+            } => match key {
+                KEY_ESC => {
+                    state.cancelled = true;
+                    state.running = false;
+                }
+                KEY_ENTER | KEY_KPENTER => state.running = false,
+                // Toggles the focused window like a click does
+                KEY_SPACE if state.multi_select => state.pending_click = true,
+                KEY_TAB if state.shift_held => {
+                    state.pending_navigation = Some(Navigation::Previous)
+                }
+                KEY_TAB => state.pending_navigation = Some(Navigation::Next),
+                KEY_LEFT | KEY_H => state.pending_navigation = Some(Navigation::Left),
+                KEY_DOWN | KEY_J => state.pending_navigation = Some(Navigation::Down),
+                KEY_UP | KEY_K => state.pending_navigation = Some(Navigation::Up),
+                KEY_RIGHT | KEY_L => state.pending_navigation = Some(Navigation::Right),
+                _ => (),
+            },
+            _ => (),
         }
     }
 }
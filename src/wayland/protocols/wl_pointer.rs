@@ -1,11 +1,17 @@
-use wayland_client::{Connection, Dispatch, Proxy, QueueHandle, protocol::wl_pointer};
+use wayland_client::{Connection, Dispatch, Proxy, QueueHandle, WEnum, protocol::wl_pointer};
 use wayland_protocols::wp::cursor_shape::v1::client::{
     wp_cursor_shape_device_v1::{self, Shape as CursorShape},
     wp_cursor_shape_manager_v1,
 };
 
+use super::{Button, Click};
 use crate::wayland::buffer_surface;
 
+/// Linux input event codes, from `linux/input-event-codes.h`.
+const BTN_LEFT: u32 = 0x110;
+const BTN_RIGHT: u32 = 0x111;
+const BTN_MIDDLE: u32 = 0x112;
+
 impl Dispatch<wl_pointer::WlPointer, ()> for super::State {
     fn event(
         state: &mut Self,
@@ -67,9 +73,31 @@ impl Dispatch<wl_pointer::WlPointer, ()> for super::State {
                 ..
             } => {
                 state.pointer_position = Some((surface_x, surface_y));
+                state.keyboard_client = None;
             }
-            wl_pointer::Event::Button { .. } => {
-                state.running = false;
+            wl_pointer::Event::Button {
+                button,
+                state: WEnum::Value(wl_pointer::ButtonState::Pressed),
+                ..
+            } => {
+                if !state.multi_select {
+                    state.click = Some(Click {
+                        button: match button {
+                            BTN_LEFT => Button::Left,
+                            BTN_RIGHT => Button::Right,
+                            BTN_MIDDLE => Button::Middle,
+                            _ => Button::Other,
+                        },
+                        shift: state.shift_held,
+                    });
+                    state.running = false;
+                } else {
+                    match button {
+                        BTN_LEFT => state.pending_click = true,
+                        BTN_RIGHT => state.running = false,
+                        _ => (),
+                    }
+                }
             }
             _ => (),
         }
@@ -1,4 +1,7 @@
 use super::super::buffer_surface::BufferSurface;
+use crate::config::OverlayColors;
+
+use hyprland::shared::Address;
 
 use std::collections::{HashMap, HashSet};
 
@@ -33,4 +36,49 @@ pub struct State {
     pub output_index: u8,
     pub pointer_position: Option<(f64, f64)>,
     pub pointer_surface: Option<(String, wl_surface::WlSurface)>,
+    pub overlay_colors: OverlayColors,
+    /// Clicks add windows to `selected` instead of ending the selection
+    pub multi_select: bool,
+    /// Windows clicked in multi-select mode, in the order they were clicked
+    pub selected: Vec<Address>,
+    /// A left click that hasn't been matched against the window regions yet
+    pub pending_click: bool,
+    /// Window moved to with the keyboard, it counts as hovered until the pointer moves
+    pub keyboard_client: Option<Address>,
+    /// A keyboard move that hasn't been matched against the window regions yet
+    pub pending_navigation: Option<Navigation>,
+    /// Esc was pressed, nothing should be picked
+    pub cancelled: bool,
+    pub shift_held: bool,
+    /// The button press that picked the window outside of multi-select mode
+    pub click: Option<Click>,
+}
+
+/// A button press on the overlay, with whether Shift was held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Click {
+    pub button: Button,
+    pub shift: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    Left,
+    Right,
+    Middle,
+    /// Side buttons and the like
+    Other,
+}
+
+/// Keyboard moves between the windows in the overlay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Navigation {
+    /// Tab, through the windows in reading order across the monitors
+    Next,
+    /// Shift-Tab
+    Previous,
+    Left,
+    Right,
+    Up,
+    Down,
 }
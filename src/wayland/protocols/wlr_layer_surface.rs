@@ -30,7 +30,13 @@ impl Dispatch<zwlr_layer_surface_v1::ZwlrLayerSurfaceV1, Option<String>> for sup
                         if let Some(mut ready_to_draw) = ready_to_draw {
                             *state.buffer_surfaces.get_mut(id).unwrap() =
                                 BufferSurface::ReadyToDraw(ready_to_draw.clone());
-                            ready_to_draw.actually_draw_buffer_surface(&shm, qh);
+                            ready_to_draw.actually_draw_buffer_surface(
+                                &shm,
+                                qh,
+                                &state.overlay_colors,
+                                &state.selected,
+                                state.keyboard_client.as_ref(),
+                            );
                         }
                     }
                 };
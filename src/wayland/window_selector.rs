@@ -1,11 +1,20 @@
 use super::buffer_surface::{BaseSurfaceBuffer, BufferSurface, ClientRegion, HasOutput, InProcess};
-use super::protocols::State;
+pub use super::protocols::{Button, Click};
+use super::protocols::{Navigation, State};
+use crate::config;
 
-use hyprland::data::{Client as HyClient, Clients as HyClients, Monitors as HyMonitors};
+use hyprland::data::{
+    Client as HyClient, Clients as HyClients, Monitor as HyMonitor, Monitors as HyMonitors,
+};
 use hyprland::shared::{Address, WorkspaceId};
-use wayland_client::EventQueue;
+use rustix::event::{PollFd, PollFlags, Timespec, poll};
+use rustix::io::Errno;
+use wayland_client::{EventQueue, backend::WaylandError};
 
 use std::collections::HashMap;
+use std::fmt;
+use std::io::ErrorKind;
+use std::time::{Duration, Instant};
 
 use wayland_client::{
     Connection, Dispatch, QueueHandle,
@@ -16,17 +25,70 @@ use wayland_protocols::wp::cursor_shape::v1::client::{
 };
 use wayland_protocols_wlr::layer_shell::v1::client::{zwlr_layer_shell_v1, zwlr_layer_surface_v1};
 
+/// Longest [`WindowSelect::update`] waits for the compositor, so the terminal UI keeps
+/// reading keys while the overlay is up.
+const WAIT: Duration = Duration::from_millis(16);
+
 #[derive(Debug)]
 pub enum Message {
-    Done,
+    Done(Selection),
     HoveredClient(Option<Address>),
+    /// Nothing happened on the overlay while waiting
+    Idle,
+}
+
+/// How the overlay was ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selection {
+    /// The window clicked on, or moved to before Enter, or every window picked in
+    /// multi-select mode. The click is there when a button press picked a single window.
+    Confirmed(Vec<Address>, Option<Click>),
+    Unpicked(Unpicked),
+}
+
+/// Why a selection ended without a window. It's an error as well, so commands can hand it up
+/// to main, which exits with [`Unpicked::exit_code`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unpicked {
+    /// Esc on the overlay or q in the terminal
+    Cancelled,
+    /// Clicked or pressed Enter over no window, or finished a multi-select with none picked
+    Nothing,
+    /// The `--timeout` ran out
+    TimedOut,
+}
+
+impl Unpicked {
+    /// What the process exits with, so wrapper scripts can tell the outcomes apart. 1 is
+    /// left to errors and 2 to clap's usage errors.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Unpicked::Cancelled => 3,
+            Unpicked::Nothing => 4,
+            Unpicked::TimedOut => 5,
+        }
+    }
+}
+
+impl fmt::Display for Unpicked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unpicked::Cancelled => write!(f, "The selection was cancelled"),
+            Unpicked::Nothing => write!(f, "Nothing was picked"),
+            Unpicked::TimedOut => write!(f, "The selection timed out"),
+        }
+    }
 }
 
+impl std::error::Error for Unpicked {}
+
 pub struct Running {
     state: State,
     event_queue: EventQueue<State>,
     client_regions: Vec<ClientRegion>,
     mapped_client_id_and_client: HashMap<Address, HyClient>,
+    /// When the selection gives up, see [`WindowSelect::timeout`]
+    deadline: Option<Instant>,
 }
 
 pub enum Desu {
@@ -46,9 +108,11 @@ impl WindowSelect {
                     &mut stuff.event_queue,
                     &mut stuff.client_regions,
                     &mut stuff.mapped_client_id_and_client,
+                    stuff.deadline,
                 );
             }
-            Desu::Done => Message::Done,
+            // Cleaned up before it ended, which abandons it
+            Desu::Done => Message::Done(Selection::Unpicked(Unpicked::Cancelled)),
         };
     }
     pub fn clean_up(self: &mut Self) {
@@ -58,6 +122,54 @@ impl WindowSelect {
         };
         self.stuff = Desu::Done;
     }
+    /// Id of the monitor the pointer is over, or of the window moved to with the keyboard, for
+    /// picking a monitor rather than a window.
+    pub fn hovered_monitor(&self) -> Option<String> {
+        match &self.stuff {
+            Desu::Running(stuff) => match &stuff.state.keyboard_client {
+                Some(client_id) => stuff
+                    .client_regions
+                    .iter()
+                    .find(|region| region.client_id == *client_id)
+                    .and_then(|region| region.monitor.clone()),
+                None => stuff
+                    .state
+                    .pointer_surface
+                    .as_ref()
+                    .map(|(monitor_id, _)| monitor_id.clone()),
+            },
+            Desu::Done => None,
+        }
+    }
+    /// Let clicks build up a set of windows, see [`Self::selected`]. Enter or a right click
+    /// ends the selection.
+    pub fn multi_select(mut self) -> Self {
+        if let Desu::Running(stuff) = &mut self.stuff {
+            stuff.state.multi_select = true;
+        }
+        self
+    }
+    /// End the selection with [`Unpicked::TimedOut`] once `timeout` has passed.
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        if let Desu::Running(stuff) = &mut self.stuff {
+            stuff.deadline = timeout.map(|timeout| Instant::now() + timeout);
+        }
+        self
+    }
+    /// Whether Shift is held down, for showing what a click would do.
+    pub fn shift_held(&self) -> bool {
+        match &self.stuff {
+            Desu::Running(stuff) => stuff.state.shift_held,
+            Desu::Done => false,
+        }
+    }
+    /// Windows clicked so far in multi-select mode, in the order they were clicked.
+    pub fn selected(&self) -> Vec<Address> {
+        match &self.stuff {
+            Desu::Running(stuff) => stuff.state.selected.clone(),
+            Desu::Done => vec![],
+        }
+    }
     pub fn new(clients: HyClients, monitors: HyMonitors) -> Self {
         let (wl_state, client_regions) = create_state_and_region_bounds(&clients, &monitors);
         return Self {
@@ -66,11 +178,68 @@ impl WindowSelect {
                 client_regions,
                 event_queue: create_wayland_window_select(),
                 mapped_client_id_and_client: index_client_id(&clients),
+                deadline: None,
             }),
         };
     }
 }
 
+/// Run the overlay on its own, without the terminal UI, and return the window that was
+/// clicked on.
+pub fn pick_client(
+    clients: HyClients,
+    monitors: HyMonitors,
+    timeout: Option<Duration>,
+) -> Result<HyClient, Unpicked> {
+    let mapped_client_id_and_client = index_client_id(&clients);
+    let mut window_select = WindowSelect::new(clients, monitors).timeout(timeout);
+    loop {
+        if let Message::Done(selection) = window_select.update() {
+            window_select.clean_up();
+            return match selection {
+                Selection::Confirmed(client_ids, _) => client_ids
+                    .first()
+                    .and_then(|client_id| mapped_client_id_and_client.get(client_id).cloned())
+                    .ok_or(Unpicked::Nothing),
+                Selection::Unpicked(unpicked) => Err(unpicked),
+            };
+        }
+    }
+}
+
+/// Like [`pick_client`] but hands back the monitor that was clicked on. A click off the
+/// windows still picks the monitor it landed on.
+pub fn pick_monitor(
+    clients: HyClients,
+    monitors: HyMonitors,
+    timeout: Option<Duration>,
+) -> Result<HyMonitor, Unpicked> {
+    let all_monitors: Vec<HyMonitor> = monitors.iter().cloned().collect();
+    let mut window_select = WindowSelect::new(clients, monitors).timeout(timeout);
+    let mut hovered_monitor_id = None;
+    loop {
+        let message = window_select.update();
+        if let Some(monitor_id) = window_select.hovered_monitor() {
+            hovered_monitor_id = Some(monitor_id);
+        }
+        if let Message::Done(selection) = message {
+            window_select.clean_up();
+            if let Selection::Unpicked(unpicked @ (Unpicked::Cancelled | Unpicked::TimedOut)) =
+                selection
+            {
+                return Err(unpicked);
+            }
+            return hovered_monitor_id
+                .and_then(|monitor_id| {
+                    all_monitors
+                        .into_iter()
+                        .find(|monitor| monitor.id.to_string() == monitor_id)
+                })
+                .ok_or(Unpicked::Nothing);
+        }
+    }
+}
+
 fn index_client_id(clients: &HyClients) -> HashMap<Address, HyClient> {
     return HashMap::<Address, HyClient>::from_iter(
         clients
@@ -84,36 +253,228 @@ fn update_running(
     event_queue: &mut EventQueue<State>,
     client_regions: &mut Vec<ClientRegion>,
     mapped_client_id_and_client: &mut HashMap<Address, HyClient>,
+    deadline: Option<Instant>,
 ) -> Message {
     if state.running {
-        event_queue.blocking_dispatch(state).expect("wayland loop");
-        if let (Some(pointer_position), Some((pointer_monitor_id, _))) =
-            (state.pointer_position, state.pointer_surface.clone())
-        {
-            let hovered_client_region = client_regions.iter().find(|client| {
-                let pointer_x = pointer_position.0.trunc() as i16;
-                let pointer_y = pointer_position.1.trunc() as i16;
-                let x = client.at.0 < pointer_x && pointer_x < (client.at.0 + client.size.0);
-                let y = client.at.1 < pointer_y && pointer_y < (client.at.1 + client.size.1);
-                if let Some(client_monitor) = &client.monitor {
-                    return x && y && client_monitor.to_string() == pointer_monitor_id;
-                }
-                return false;
-            });
-            let hovered_client = match hovered_client_region {
-                Some(hovered_client_region) => {
-                    mapped_client_id_and_client.get(&hovered_client_region.client_id)
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            state.running = false;
+            return Message::Done(Selection::Unpicked(Unpicked::TimedOut));
+        }
+        let drawn_keyboard_client = state.keyboard_client.clone();
+        if dispatch(event_queue, state) == 0 {
+            return Message::Idle;
+        }
+
+        let pointer_client =
+            pointer_client_region(state, client_regions).map(|region| region.client_id.clone());
+        if let Some(navigation) = state.pending_navigation.take() {
+            let from = state.keyboard_client.clone().or(pointer_client.clone());
+            state.keyboard_client = navigate(
+                client_regions,
+                mapped_client_id_and_client,
+                from.as_ref(),
+                navigation,
+            );
+        }
+        let hovered_client = state.keyboard_client.clone().or(pointer_client);
+
+        let mut redraw = state.keyboard_client != drawn_keyboard_client;
+        if state.pending_click {
+            state.pending_click = false;
+            // A click off the windows has nothing to toggle
+            if let Some(clicked) = &hovered_client {
+                match state
+                    .selected
+                    .iter()
+                    .position(|client_id| client_id == clicked)
+                {
+                    Some(index) => _ = state.selected.remove(index),
+                    None => state.selected.push(clicked.clone()),
                 }
-                None => None,
-            };
-            return match hovered_client {
-                Some(client) => Message::HoveredClient(Some(client.address.clone())),
-                None => Message::HoveredClient(None),
-            };
-        };
-        return Message::HoveredClient(None);
+                redraw = true;
+            }
+        }
+        if redraw {
+            redraw_surfaces(state, &event_queue.handle());
+        }
+
+        let hovered_client =
+            hovered_client.filter(|client_id| mapped_client_id_and_client.contains_key(client_id));
+        if state.running {
+            return Message::HoveredClient(hovered_client);
+        }
+        return Message::Done(selection(state, hovered_client));
     } else {
-        return Message::Done;
+        let hovered_client = state
+            .keyboard_client
+            .clone()
+            .or_else(|| {
+                pointer_client_region(state, client_regions).map(|region| region.client_id.clone())
+            })
+            .filter(|client_id| mapped_client_id_and_client.contains_key(client_id));
+        return Message::Done(selection(state, hovered_client));
+    }
+}
+
+/// What the overlay ended with, `hovered_client` being the window under the pointer or moved
+/// to with the keyboard.
+fn selection(state: &State, hovered_client: Option<Address>) -> Selection {
+    if state.cancelled {
+        return Selection::Unpicked(Unpicked::Cancelled);
+    }
+    let picked: Vec<Address> = match state.multi_select {
+        true => state.selected.clone(),
+        false => hovered_client.into_iter().collect(),
+    };
+    match picked.is_empty() {
+        true => Selection::Unpicked(Unpicked::Nothing),
+        false => Selection::Confirmed(picked, state.click),
+    }
+}
+
+/// Handle whatever the compositor has sent, waiting up to [`WAIT`] for it. Returns how many
+/// events were handled.
+fn dispatch(event_queue: &mut EventQueue<State>, state: &mut State) -> usize {
+    let dispatched = event_queue.dispatch_pending(state).expect("wayland loop");
+    if dispatched > 0 {
+        return dispatched;
+    }
+    event_queue.flush().expect("wayland loop");
+    // Another reader already has the events queued up when there's no guard
+    if let Some(guard) = event_queue.prepare_read() {
+        let ready = {
+            let connection_fd = guard.connection_fd();
+            let mut fds = [PollFd::new(&connection_fd, PollFlags::IN)];
+            let timeout = Timespec::try_from(WAIT).ok();
+            match poll(&mut fds, timeout.as_ref()) {
+                Ok(ready) => ready,
+                Err(Errno::INTR) => 0,
+                Err(error) => panic!("wayland loop: {error}"),
+            }
+        };
+        if ready > 0 {
+            match guard.read() {
+                Ok(_) => {}
+                Err(WaylandError::Io(error)) if error.kind() == ErrorKind::WouldBlock => {}
+                Err(error) => panic!("wayland loop: {error}"),
+            }
+        }
+    }
+    event_queue.dispatch_pending(state).expect("wayland loop")
+}
+
+fn pointer_client_region<'r>(
+    state: &State,
+    client_regions: &'r [ClientRegion],
+) -> Option<&'r ClientRegion> {
+    let (Some(pointer_position), Some((pointer_monitor_id, _))) =
+        (state.pointer_position, &state.pointer_surface)
+    else {
+        return None;
+    };
+    client_regions.iter().find(|client| {
+        let pointer_x = pointer_position.0.trunc() as i16;
+        let pointer_y = pointer_position.1.trunc() as i16;
+        let x = client.at.0 < pointer_x && pointer_x < (client.at.0 + client.size.0);
+        let y = client.at.1 < pointer_y && pointer_y < (client.at.1 + client.size.1);
+        if let Some(client_monitor) = &client.monitor {
+            return x && y && client_monitor == pointer_monitor_id;
+        }
+        return false;
+    })
+}
+
+/// A window's place in the layout of all monitors, for moving between windows.
+struct WindowPlace<'r> {
+    client_id: &'r Address,
+    /// Top left corner of the window's monitor
+    monitor_at: (i32, i32),
+    center: (i32, i32),
+}
+
+/// The window `navigation` moves to from `from`. With nothing focused yet Tab and the
+/// direction keys start at the first window, Shift-Tab at the last.
+fn navigate(
+    client_regions: &[ClientRegion],
+    mapped_client_id_and_client: &HashMap<Address, HyClient>,
+    from: Option<&Address>,
+    navigation: Navigation,
+) -> Option<Address> {
+    let mut places: Vec<WindowPlace> = client_regions
+        .iter()
+        .filter(|region| region.monitor.is_some())
+        .filter_map(|region| {
+            let client = mapped_client_id_and_client.get(&region.client_id)?;
+            // Regions are relative to their monitor, clients to the whole layout
+            let at = (client.at.0 as i32, client.at.1 as i32);
+            Some(WindowPlace {
+                client_id: &region.client_id,
+                monitor_at: (at.0 - region.at.0 as i32, at.1 - region.at.1 as i32),
+                center: (
+                    at.0 + client.size.0 as i32 / 2,
+                    at.1 + client.size.1 as i32 / 2,
+                ),
+            })
+        })
+        .collect();
+    // Reading order: monitor by monitor, then top to bottom and left to right
+    places.sort_by_key(|place| {
+        (
+            place.monitor_at,
+            place.center.1 - place.monitor_at.1,
+            place.center.0 - place.monitor_at.0,
+        )
+    });
+
+    let current = from.and_then(|from| places.iter().position(|place| place.client_id == from));
+    let Some(current) = current else {
+        let place = match navigation {
+            Navigation::Previous => places.last(),
+            _ => places.first(),
+        };
+        return place.map(|place| place.client_id.clone());
+    };
+
+    let count = places.len();
+    let center = places[current].center;
+    let next = match navigation {
+        Navigation::Next => Some(&places[(current + 1) % count]),
+        Navigation::Previous => Some(&places[(current + count - 1) % count]),
+        // The nearest window that way, going straight counting more than going sideways
+        direction => places
+            .iter()
+            .filter_map(|place| {
+                let offset = (place.center.0 - center.0, place.center.1 - center.1);
+                let (ahead, sideways) = match direction {
+                    Navigation::Left => (-offset.0, offset.1),
+                    Navigation::Right => (offset.0, offset.1),
+                    Navigation::Up => (-offset.1, offset.0),
+                    _ => (offset.1, offset.0),
+                };
+                (ahead > 0).then_some((ahead + 2 * sideways.abs(), place))
+            })
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, place)| place),
+    };
+    next.or(Some(&places[current]))
+        .map(|place| place.client_id.clone())
+}
+
+/// Draw every overlay surface again, after the multi-select set or the keyboard focus changed.
+fn redraw_surfaces(state: &mut State, qh: &QueueHandle<State>) {
+    let Some(shm) = state.shm.clone() else {
+        return;
+    };
+    for buffer_surface in state.buffer_surfaces.values_mut() {
+        if let BufferSurface::ReadyToDraw(ready_to_draw) = buffer_surface {
+            ready_to_draw.actually_draw_buffer_surface(
+                &shm,
+                qh,
+                &state.overlay_colors,
+                &state.selected,
+                state.keyboard_client.as_ref(),
+            );
+        }
     }
 }
 
@@ -352,6 +713,15 @@ fn create_state_and_region_bounds<'c>(
             output_index: 0,
             pointer_position: None,
             pointer_surface: None,
+            overlay_colors: config::get().overlay,
+            multi_select: false,
+            selected: vec![],
+            pending_click: false,
+            keyboard_client: None,
+            pending_navigation: None,
+            cancelled: false,
+            shift_held: false,
+            click: None,
         },
         client_regions.collect(),
     );
@@ -368,3 +738,92 @@ fn create_wayland_window_select() -> EventQueue<State> {
 
     return event_queue;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_client::client;
+
+    /// Two side by side monitors: the left one split into a window on the left half and two
+    /// stacked on the right, the right one with a single window a bit lower down, plus a
+    /// window on no monitor.
+    fn layout() -> (Vec<ClientRegion>, HashMap<Address, HyClient>) {
+        let windows = [
+            ("0xa1", (0, 0), (960, 1080), Some(((0, 0), "0"))),
+            ("0xa2", (960, 0), (960, 540), Some(((0, 0), "0"))),
+            ("0xa3", (960, 540), (960, 540), Some(((0, 0), "0"))),
+            ("0xb1", (1920, 100), (1920, 980), Some(((1920, 0), "1"))),
+            ("0xc1", (0, 0), (100, 100), None),
+        ];
+        let mut regions = vec![];
+        let mut clients = HashMap::new();
+        for (address, at, size, monitor) in windows {
+            let mut hy_client = client(address, "kitty", address);
+            hy_client.at = at;
+            hy_client.size = size;
+            let region = ClientRegion {
+                at: match monitor {
+                    Some(((x, y), _)) => (at.0 - x, at.1 - y),
+                    None => at,
+                },
+                size,
+                monitor: monitor.map(|(_, id)| id.to_string()),
+                client_id: hy_client.address.clone(),
+            };
+            clients.insert(hy_client.address.clone(), hy_client);
+            regions.push(region);
+        }
+        (regions, clients)
+    }
+
+    fn step(from: Option<&str>, navigation: Navigation) -> String {
+        let (regions, clients) = layout();
+        let from = from.map(Address::new);
+        navigate(&regions, &clients, from.as_ref(), navigation)
+            .expect("a window to move to")
+            .to_string()
+    }
+
+    #[test]
+    fn tab_goes_in_reading_order_and_wraps() {
+        // Monitor by monitor, then by the window's center from the top
+        let order = ["0xa2", "0xa1", "0xa3", "0xb1"];
+        for (index, from) in order.iter().enumerate() {
+            let next = order[(index + 1) % order.len()];
+            assert_eq!(step(Some(from), Navigation::Next), next);
+            assert_eq!(step(Some(next), Navigation::Previous), *from);
+        }
+    }
+
+    #[test]
+    fn starts_at_either_end_without_focus() {
+        assert_eq!(step(None, Navigation::Next), "0xa2");
+        assert_eq!(step(None, Navigation::Right), "0xa2");
+        assert_eq!(step(None, Navigation::Previous), "0xb1");
+        // A window the overlay doesn't show counts as nothing focused
+        assert_eq!(step(Some("0xc1"), Navigation::Next), "0xa2");
+    }
+
+    #[test]
+    fn arrows_pick_the_nearest_window_that_way() {
+        assert_eq!(step(Some("0xa2"), Navigation::Down), "0xa3");
+        assert_eq!(step(Some("0xa3"), Navigation::Up), "0xa2");
+        assert_eq!(step(Some("0xa2"), Navigation::Left), "0xa1");
+        // A window off to the side still counts when its center is further that way
+        assert_eq!(step(Some("0xa1"), Navigation::Down), "0xa3");
+    }
+
+    #[test]
+    fn arrows_cross_the_monitor_edge() {
+        assert_eq!(step(Some("0xa3"), Navigation::Right), "0xb1");
+        // Of the windows on the left monitor, the one closest to straight ahead
+        assert_eq!(step(Some("0xb1"), Navigation::Left), "0xa3");
+    }
+
+    #[test]
+    fn arrows_stay_put_with_nothing_that_way() {
+        assert_eq!(step(Some("0xb1"), Navigation::Right), "0xb1");
+        assert_eq!(step(Some("0xa2"), Navigation::Up), "0xa2");
+        assert_eq!(step(Some("0xa1"), Navigation::Left), "0xa1");
+    }
+}